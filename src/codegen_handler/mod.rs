@@ -0,0 +1,86 @@
+use anyhow::Result;
+use crate::RustCollection;
+use crate::dictionary_handler::{Definition, Word};
+use crate::output_handler::{OutputWriter, WordSense};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+
+/// Writes a Rust source file embedding the words and their definitions, for including at compile time.
+///
+/// Each word maps to a slice of (part of speech, definition, ILI) tuples in sense order. Slices are sorted by
+/// word so they can be searched with `binary_search_by_key`, and maps need the phf crate.
+pub struct RustWriter{
+    output_path: PathBuf,
+    collection: RustCollection,
+    entries: Vec<String>
+}
+
+impl RustWriter{
+    pub fn new(output_path: &Path, collection: RustCollection) -> RustWriter{
+        RustWriter { output_path: output_path.to_owned(), collection, entries: vec![] }
+    }
+}
+
+impl OutputWriter for RustWriter{
+    fn label(&self) -> &str{
+        "Rust source"
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating Rust source...");
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, _id: i64, _definition: &Definition) -> Result<()>{
+        Ok(())
+    }
+
+    fn write_word(&mut self, _id: i64, word: &str, _entry: &Word, senses: &[WordSense]) -> Result<()>{
+        // Debug formatting gives escaped string literals
+        let definitions: Vec<String> = senses.iter()
+            .map(|sense| format!("({:?}, {:?}, {:?})", sense.definition.part_of_speech.name(), sense.definition.data, sense.definition.ili))
+            .collect();
+
+        let entry: String = match self.collection{
+            RustCollection::Slice => format!("    ({:?}, &[{}]),\n", word, definitions.join(", ")),
+            RustCollection::Phf => format!("    {:?} => &[{}],\n", word, definitions.join(", "))
+        };
+
+        self.entries.push(entry);
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        let mut source: String = String::from("// Generated by wordnet2db. Do not edit.\n\n");
+
+        match self.collection{
+            RustCollection::Slice => {
+                source.push_str("/// Words in sorted order with the part of speech, definition and ILI identifier of each sense\n");
+                source.push_str("pub static WORDS: &[(&str, &[(&str, &str, Option<&str>)])] = &[\n");
+                source.push_str(&self.entries.concat());
+                source.push_str("];\n\n");
+                source.push_str("/// Part of speech, definition and ILI identifier of each sense of a word\n");
+                source.push_str("pub fn lookup(word: &str) -> Option<&'static [(&'static str, &'static str, Option<&'static str>)]> {\n");
+                source.push_str("    WORDS.binary_search_by_key(&word, |(word, _)| word).ok().map(|index| WORDS[index].1)\n");
+                source.push_str("}\n");
+            }
+            RustCollection::Phf => {
+                source.push_str("/// Words with the part of speech, definition and ILI identifier of each sense\n");
+                source.push_str("pub static WORDS: phf::Map<&'static str, &'static [(&'static str, &'static str, Option<&'static str>)]> = phf::phf_map! {\n");
+                source.push_str(&self.entries.concat());
+                source.push_str("};\n");
+            }
+        }
+
+        // Save to file
+        let mut file = File::create(self.output_path.join("dictionary.rs"))?;
+        file.write_all(source.as_bytes())?;
+
+        Ok(())
+    }
+}
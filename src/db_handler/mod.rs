@@ -1,193 +1,959 @@
 use anyhow::Result;
-use crate::dictionary_handler::WordData;
-use rusqlite::{Connection, Transaction};
-use std::collections::{hash_map::Entry, HashMap};
+use crate::{PartOfSpeech, Schema};
+use crate::dictionary_handler::{self, Definition, Word, VERB_FRAMES};
+use crate::output_handler::{OutputWriter, Relation, SenseRelation, WordSense};
+use rusqlite::Connection;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 
-pub fn create_word_database(output_path: &Path, word_data: WordData) -> Result<()>{
-    // Print status message
-    println!("Creating database...");
-
-    let (definitions, words) = word_data;
-
-    // Open a connection
-    let mut conn: Connection = Connection::open(output_path.join("dictionary.sqlite3"))?;
-
-    // Start a transaction
-    let transaction: Transaction = conn.transaction()?;
-
-    {
-        // Create tables
-        transaction.execute(
-            "CREATE TABLE definition (
-                    id              INTEGER PRIMARY KEY AUTOINCREMENT,
-                    data            TEXT,
-                    part_of_speech  TEXT NOT NULL
-            )",
-            ()
-        )?;
-
-        transaction.execute(
-            "CREATE TABLE word (
-                    id      INTEGER PRIMARY KEY AUTOINCREMENT,
+const CREATE_PART_OF_SPEECH_TABLE: &str = "CREATE TABLE part_of_speech (
+                    id      INTEGER PRIMARY KEY,
                     data    TEXT NOT NULL
-            )",
-            ()
-        )?;
+            )";
+
+const CREATE_DEFINITION_TABLE: &str = "CREATE TABLE definition (
+                    id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+                    data                TEXT,
+                    part_of_speech_id   INTEGER NOT NULL,
+                    lexname             TEXT,
+                    depth               INTEGER,
+                    ili                 TEXT,
+                    FOREIGN KEY (part_of_speech_id) REFERENCES part_of_speech(id)
+            )";
+
+const CREATE_DEFINITION_ILI_INDEX: &str = "CREATE INDEX definition_ili ON definition (ili)";
+
+const CREATE_WORD_TABLE: &str = "CREATE TABLE word (
+                    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                    data        TEXT NOT NULL,
+                    display     TEXT NOT NULL,
+                    proper_noun INTEGER NOT NULL,
+                    signature   TEXT NOT NULL,
+                    frequency   INTEGER NOT NULL
+            )";
+
+/// Sound-alike keys, added to the word table when requested
+const ADD_WORD_PHONETIC_COLUMNS: [&str; 3] = [
+    "ALTER TABLE word ADD COLUMN soundex TEXT",
+    "ALTER TABLE word ADD COLUMN metaphone TEXT",
+    "ALTER TABLE word ADD COLUMN metaphone_alternate TEXT"
+];
+
+const CREATE_WORD_PHONETIC_INDEXES: [&str; 3] = [
+    "CREATE INDEX word_soundex ON word (soundex)",
+    "CREATE INDEX word_metaphone ON word (metaphone)",
+    "CREATE INDEX word_metaphone_alternate ON word (metaphone_alternate)"
+];
+
+const CREATE_PRONUNCIATION_TABLE: &str = "CREATE TABLE pronunciation (
+                    word_id     INTEGER NOT NULL,
+                    variant     INTEGER NOT NULL,
+                    phonemes    TEXT NOT NULL,
+                    stress      TEXT NOT NULL,
+                    syllables   INTEGER NOT NULL,
+                    rhyme       TEXT NOT NULL,
+                    PRIMARY KEY (word_id, variant),
+                    FOREIGN KEY (word_id) REFERENCES word(id)
+            )";
+
+const CREATE_PRONUNCIATION_RHYME_INDEX: &str = "CREATE INDEX pronunciation_rhyme ON pronunciation (rhyme)";
 
-        transaction.execute(
-            "CREATE TABLE word_definition (
+const CREATE_WORD_DEFINITION_TABLE: &str = "CREATE TABLE word_definition (
                     id              INTEGER PRIMARY KEY AUTOINCREMENT,
                     definition_id   INTEGER,
                     word_id         INTEGER,
+                    frequency       INTEGER NOT NULL,
+                    form            TEXT NOT NULL,
+                    marker          TEXT,
                     FOREIGN KEY (definition_id) REFERENCES definition(id),
                     FOREIGN KEY (word_id) REFERENCES word(id)
-            )",
-            ()
-        )?;
-
-        // Prepare insert statements
-        let mut insert_definition = transaction.prepare("INSERT INTO definition (data, part_of_speech) VALUES (?, ?)")?;
-        let mut insert_word = transaction.prepare("INSERT INTO word (data) VALUES (?)")?;
-        let mut insert_word_definition = transaction.prepare("INSERT INTO word_definition (definition_id, word_id) VALUES (?, ?)")?;
-
-        // Create a hashmap to store offset and id values
-        let mut definition_ids: HashMap<u64, i64> = HashMap::new();
-
-        // Create an integer to store the IDs
-        let mut definition_id: i64 = 1;
-        let mut word_id: i64 = 1;
-
-        // Process words and definitions
-        for (word, offsets) in words{
-            // Insert word
-            insert_word.execute([&word])?;
-
-            // Insert definitions
-            for offset in offsets{
-                if let Entry::Vacant(e) = definition_ids.entry(offset){
-                    // Get definition
-                    let definition_option = definitions.get(&offset);
-
-                    if let Some(definition) = definition_option{
-                        // Insert definition
-                        insert_definition.execute([&definition.data, &definition.part_of_speech])?;
-
-                        // Create an offset/id association
-                        e.insert(definition_id);
-
-                        // Increment definition id
-                        definition_id += 1;
-                    }
+            )";
+
+const CREATE_SYNSET_RELATION_TABLE: &str = "CREATE TABLE synset_relation (
+                    source_id   INTEGER NOT NULL,
+                    target_id   INTEGER NOT NULL,
+                    relation    TEXT NOT NULL,
+                    FOREIGN KEY (source_id) REFERENCES definition(id),
+                    FOREIGN KEY (target_id) REFERENCES definition(id)
+            )";
+
+const CREATE_SYNSET_RELATION_INDEXES: [&str; 2] = [
+    "CREATE INDEX synset_relation_source ON synset_relation (source_id, relation)",
+    "CREATE INDEX synset_relation_target ON synset_relation (target_id, relation)"
+];
+
+const CREATE_SENSE_RELATION_TABLE: &str = "CREATE TABLE sense_relation (
+                    source_id   INTEGER NOT NULL,
+                    target_id   INTEGER NOT NULL,
+                    relation    TEXT NOT NULL,
+                    FOREIGN KEY (source_id) REFERENCES word_definition(id),
+                    FOREIGN KEY (target_id) REFERENCES word_definition(id)
+            )";
+
+const CREATE_SENSE_RELATION_INDEX: &str = "CREATE INDEX sense_relation_source ON sense_relation (source_id)";
+
+const CREATE_ADJECTIVE_CLUSTER_TABLE: &str = "CREATE TABLE adjective_cluster (
+                    satellite_id    INTEGER PRIMARY KEY,
+                    head_id         INTEGER NOT NULL,
+                    FOREIGN KEY (satellite_id) REFERENCES definition(id),
+                    FOREIGN KEY (head_id) REFERENCES definition(id)
+            )";
+
+/// Satellites have no antonyms of their own, so go through the antonyms of their head's words (e.g. arid to wet through dry)
+const CREATE_SATELLITE_ANTONYM_VIEW: &str = "CREATE VIEW satellite_antonym AS
+                    SELECT cluster.satellite_id, antonym.definition_id AS antonym_id
+                    FROM adjective_cluster cluster
+                    JOIN word_definition head ON head.definition_id = cluster.head_id
+                    JOIN sense_relation relation ON relation.source_id = head.id AND relation.relation = 'antonym'
+                    JOIN word_definition antonym ON antonym.id = relation.target_id";
+
+const CREATE_VERB_FRAME_TABLE: &str = "CREATE TABLE verb_frame (
+                    id      INTEGER PRIMARY KEY,
+                    data    TEXT NOT NULL
+            )";
+
+const CREATE_WORD_DEFINITION_VERB_FRAME_TABLE: &str = "CREATE TABLE word_definition_verb_frame (
+                    word_definition_id  INTEGER NOT NULL,
+                    verb_frame_id       INTEGER NOT NULL,
+                    PRIMARY KEY (word_definition_id, verb_frame_id),
+                    FOREIGN KEY (word_definition_id) REFERENCES word_definition(id),
+                    FOREIGN KEY (verb_frame_id) REFERENCES verb_frame(id)
+            )";
+
+const CREATE_VERB_SENTENCE_TABLE: &str = "CREATE TABLE verb_sentence (
+                    id      INTEGER PRIMARY KEY,
+                    data    TEXT NOT NULL
+            )";
+
+const CREATE_WORD_DEFINITION_VERB_SENTENCE_TABLE: &str = "CREATE TABLE word_definition_verb_sentence (
+                    word_definition_id  INTEGER NOT NULL,
+                    verb_sentence_id    INTEGER NOT NULL,
+                    PRIMARY KEY (word_definition_id, verb_sentence_id),
+                    FOREIGN KEY (word_definition_id) REFERENCES word_definition(id),
+                    FOREIGN KEY (verb_sentence_id) REFERENCES verb_sentence(id)
+            )";
+
+const CREATE_TRANSLATION_TABLE: &str = "CREATE TABLE translation (
+                    synset_id   INTEGER NOT NULL,
+                    lang        TEXT NOT NULL,
+                    lemma       TEXT NOT NULL,
+                    PRIMARY KEY (synset_id, lang, lemma),
+                    FOREIGN KEY (synset_id) REFERENCES definition(id)
+            )";
+
+const CREATE_TRANSLATION_INDEX: &str = "CREATE INDEX translation_lemma ON translation (lang, lemma)";
+
+const CREATE_SYNSET_ANCESTOR_TABLE: &str = "CREATE TABLE synset_ancestor (
+                    synset_id       INTEGER NOT NULL,
+                    ancestor_id     INTEGER NOT NULL,
+                    depth           INTEGER NOT NULL,
+                    PRIMARY KEY (synset_id, ancestor_id),
+                    FOREIGN KEY (synset_id) REFERENCES definition(id),
+                    FOREIGN KEY (ancestor_id) REFERENCES definition(id)
+            )";
+
+const CREATE_SYNSET_ANCESTOR_INDEX: &str = "CREATE INDEX synset_ancestor_ancestor ON synset_ancestor (ancestor_id)";
+
+
+const CREATE_FLAT_ENTRY_TABLE: &str = "CREATE TABLE entry (
+                    word            TEXT NOT NULL,
+                    pos             TEXT NOT NULL,
+                    sense_number    INTEGER NOT NULL,
+                    definition      TEXT,
+                    ili             TEXT,
+                    PRIMARY KEY (word, pos, sense_number)
+            )";
+
+const CREATE_WORD_JSON_TABLE: &str = "CREATE TABLE word_json (
+                    word    TEXT PRIMARY KEY,
+                    data    TEXT NOT NULL
+            )";
+
+
+/// Double up on single quotes so a value can be placed in an SQL string literal
+fn escape(value: &str) -> String{
+    value.replace('\'', "''")
+}
+
+
+/// Render an optional value as an SQL string literal or NULL
+fn text_or_null(value: Option<&str>) -> String{
+    match value{
+        Some(value) => format!("'{}'", escape(value)),
+        None => "NULL".to_string()
+    }
+}
+
+
+pub struct SqliteWriter{
+    conn: Connection,
+    schema: Schema,
+    with_closure: bool,
+    phonetic: bool,
+    pos_codes: bool,
+    verb_sentence_ids: HashSet<u32>
+}
+
+impl SqliteWriter{
+    pub fn new(output_path: &Path, schema: Schema, with_closure: bool, phonetic: bool, pos_codes: bool) -> Result<SqliteWriter>{
+        // Open a connection
+        let conn: Connection = Connection::open(output_path.join("dictionary.sqlite3"))?;
+
+        Ok(SqliteWriter { conn, schema, with_closure, phonetic, pos_codes, verb_sentence_ids: HashSet::new() })
+    }
+
+    fn create_normalized_tables(&self) -> Result<()>{
+        self.conn.execute(CREATE_PART_OF_SPEECH_TABLE, ())?;
+        self.conn.execute(CREATE_DEFINITION_TABLE, ())?;
+        self.conn.execute(CREATE_DEFINITION_ILI_INDEX, ())?;
+        self.conn.execute(CREATE_WORD_TABLE, ())?;
+        self.conn.execute(CREATE_PRONUNCIATION_TABLE, ())?;
+        self.conn.execute(CREATE_PRONUNCIATION_RHYME_INDEX, ())?;
+        self.conn.execute(CREATE_WORD_DEFINITION_TABLE, ())?;
+        self.conn.execute(CREATE_SYNSET_RELATION_TABLE, ())?;
+
+        for statement in CREATE_SYNSET_RELATION_INDEXES{
+            self.conn.execute(statement, ())?;
+        }
+
+        self.conn.execute(CREATE_SENSE_RELATION_TABLE, ())?;
+        self.conn.execute(CREATE_SENSE_RELATION_INDEX, ())?;
+        self.conn.execute(CREATE_ADJECTIVE_CLUSTER_TABLE, ())?;
+        self.conn.execute(CREATE_SATELLITE_ANTONYM_VIEW, ())?;
+        self.conn.execute(CREATE_VERB_FRAME_TABLE, ())?;
+        self.conn.execute(CREATE_WORD_DEFINITION_VERB_FRAME_TABLE, ())?;
+        self.conn.execute(CREATE_VERB_SENTENCE_TABLE, ())?;
+        self.conn.execute(CREATE_WORD_DEFINITION_VERB_SENTENCE_TABLE, ())?;
+        self.conn.execute(CREATE_TRANSLATION_TABLE, ())?;
+        self.conn.execute(CREATE_TRANSLATION_INDEX, ())?;
+
+        // Insert parts of speech
+        for part_of_speech in PartOfSpeech::ALL{
+            self.conn.execute("INSERT INTO part_of_speech (id, data) VALUES (?, ?)", (part_of_speech.id(), part_of_speech.label(self.pos_codes)))?;
+        }
+
+        // Insert verb frame templates
+        for (id, template) in (1..).zip(VERB_FRAMES){
+            self.conn.execute("INSERT INTO verb_frame (id, data) VALUES (?, ?)", (id, template))?;
+        }
+
+        if self.phonetic{
+            for statement in ADD_WORD_PHONETIC_COLUMNS.iter().chain(&CREATE_WORD_PHONETIC_INDEXES){
+                self.conn.execute(statement, ())?;
+            }
+        }
+
+        if self.with_closure{
+            self.conn.execute(CREATE_SYNSET_ANCESTOR_TABLE, ())?;
+            self.conn.execute(CREATE_SYNSET_ANCESTOR_INDEX, ())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl OutputWriter for SqliteWriter{
+    fn label(&self) -> &str{
+        "Database"
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating database...");
+
+        // Start a transaction
+        self.conn.execute_batch("BEGIN TRANSACTION")?;
+
+        // Create tables
+        match self.schema{
+            Schema::Normalized => self.create_normalized_tables()?,
+            Schema::Flat => {
+                self.conn.execute(CREATE_FLAT_ENTRY_TABLE, ())?;
+            }
+            Schema::Kv => {
+                self.conn.execute(CREATE_WORD_JSON_TABLE, ())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, id: i64, definition: &Definition) -> Result<()>{
+        // Definitions are written with their words in the other schemas
+        if self.schema != Schema::Normalized{
+            return Ok(());
+        }
+
+        // Insert definition
+        self.conn.prepare_cached("INSERT INTO definition (id, data, part_of_speech_id, lexname, depth, ili) VALUES (?, ?, ?, ?, ?, ?)")?
+            .execute((id, &definition.data, definition.part_of_speech.id(), &definition.lexname, definition.depth, &definition.ili))?;
+
+        // Insert translations
+        let mut insert_translation = self.conn.prepare_cached("INSERT INTO translation (synset_id, lang, lemma) VALUES (?, ?, ?)")?;
+
+        for (lang, lemmas) in &definition.translations{
+            for lemma in lemmas{
+                insert_translation.execute((id, lang, lemma))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_word(&mut self, id: i64, word: &str, entry: &Word, senses: &[WordSense]) -> Result<()>{
+        match self.schema{
+            Schema::Normalized => {}
+            Schema::Flat => {
+                // Insert a row for every sense, numbered as in the index files
+                let mut insert_entry = self.conn.prepare_cached("INSERT INTO entry (word, pos, sense_number, definition, ili) VALUES (?, ?, ?, ?, ?)")?;
+
+                for sense in senses{
+                    insert_entry.execute((word, sense.definition.part_of_speech.label(self.pos_codes), sense.sense.sense_number, &sense.definition.data, &sense.definition.ili))?;
                 }
 
-                // Add entry to associative table
-                if let Some(id) = definition_ids.get(&offset){
-                    insert_word_definition.execute([id, &word_id])?;
+                return Ok(());
+            }
+            Schema::Kv => {
+                // Insert the word as JSON
+                self.conn.prepare_cached("INSERT INTO word_json (word, data) VALUES (?, ?)")?
+                    .execute((word, dictionary_handler::word_to_json(word, entry, senses, self.pos_codes)?))?;
+
+                return Ok(());
+            }
+        }
+
+        // Insert word
+        self.conn.prepare_cached("INSERT INTO word (id, data, display, proper_noun, signature, frequency) VALUES (?, ?, ?, ?, ?, ?)")?
+            .execute((id, word, &entry.display, entry.proper_noun, &entry.signature, entry.frequency))?;
+
+        // Add its sound-alike keys
+        if self.phonetic{
+            let keys = entry.phonetic.as_ref();
+            self.conn.prepare_cached("UPDATE word SET soundex = ?, metaphone = ?, metaphone_alternate = ? WHERE id = ?")?
+                .execute((keys.map(|keys| &keys.soundex), keys.map(|keys| &keys.metaphone), keys.map(|keys| &keys.metaphone_alternate), id))?;
+        }
+
+        // Insert pronunciations
+        let mut insert_pronunciation = self.conn.prepare_cached("INSERT INTO pronunciation (word_id, variant, phonemes, stress, syllables, rhyme) VALUES (?, ?, ?, ?, ?, ?)")?;
+
+        for (variant, pronunciation) in entry.pronunciations.iter().enumerate(){
+            insert_pronunciation.execute((id, variant, &pronunciation.phonemes, &pronunciation.stress, pronunciation.syllables, &pronunciation.rhyme))?;
+        }
+
+        // Add entries to associative table
+        let mut insert_word_definition = self.conn.prepare_cached("INSERT INTO word_definition (id, definition_id, word_id, frequency, form, marker) VALUES (?, ?, ?, ?, ?, ?)")?;
+        let mut insert_verb_frame = self.conn.prepare_cached("INSERT INTO word_definition_verb_frame (word_definition_id, verb_frame_id) VALUES (?, ?)")?;
+        let mut insert_verb_sentence = self.conn.prepare_cached("INSERT INTO verb_sentence (id, data) VALUES (?, ?)")?;
+        let mut insert_word_definition_sentence = self.conn.prepare_cached("INSERT INTO word_definition_verb_sentence (word_definition_id, verb_sentence_id) VALUES (?, ?)")?;
+
+        for sense in senses{
+            insert_word_definition.execute((sense.id, sense.synset_id, id, sense.sense.frequency, &sense.sense.form, &sense.sense.marker))?;
+
+            // Link the verb frames and example sentences of the sense
+            for frame in &sense.sense.frames{
+                insert_verb_frame.execute((sense.id, frame))?;
+            }
+
+            for (number, sentence) in &sense.sense.sentences{
+                if self.verb_sentence_ids.insert(*number){
+                    insert_verb_sentence.execute((number, sentence))?;
                 }
+
+                insert_word_definition_sentence.execute((sense.id, number))?;
             }
+        }
+
+        Ok(())
+    }
 
-            // Increment word id
-            word_id += 1;
+    fn write_relation(&mut self, relation: &Relation) -> Result<()>{
+        if self.schema != Schema::Normalized{
+            return Ok(());
         }
+
+        // Insert semantic relation
+        self.conn.prepare_cached("INSERT INTO synset_relation (source_id, target_id, relation) VALUES (?, ?, ?)")?
+            .execute((relation.source_id, relation.target_id, relation.pointer.relation_name()))?;
+
+        Ok(())
     }
 
-    // Commit transaction
-    transaction.commit()?;
+    fn write_sense_relation(&mut self, relation: &SenseRelation) -> Result<()>{
+        if self.schema != Schema::Normalized{
+            return Ok(());
+        }
 
-    Ok(())
+        // Insert lexical relation
+        self.conn.prepare_cached("INSERT INTO sense_relation (source_id, target_id, relation) VALUES (?, ?, ?)")?
+            .execute((relation.source_id, relation.target_id, relation.pointer.relation_name()))?;
+
+        Ok(())
+    }
+
+    fn write_adjective_cluster(&mut self, head_id: i64, satellite_id: i64) -> Result<()>{
+        if self.schema != Schema::Normalized{
+            return Ok(());
+        }
+
+        // Insert cluster membership
+        self.conn.prepare_cached("INSERT INTO adjective_cluster (satellite_id, head_id) VALUES (?, ?)")?
+            .execute((satellite_id, head_id))?;
+
+        Ok(())
+    }
+
+    fn write_ancestor(&mut self, synset_id: i64, ancestor_id: i64, depth: u32) -> Result<()>{
+        if self.schema != Schema::Normalized{
+            return Ok(());
+        }
+
+        // Insert ancestor
+        self.conn.prepare_cached("INSERT INTO synset_ancestor (synset_id, ancestor_id, depth) VALUES (?, ?, ?)")?
+            .execute((synset_id, ancestor_id, depth))?;
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        // Commit transaction
+        self.conn.execute_batch("COMMIT")?;
+
+        Ok(())
+    }
 }
 
 
-pub fn dump_sql(output_path: &Path, word_data: WordData) -> Result<()>{
-    // Print status message
-    println!("Creating SQL...");
+pub struct SqlWriter{
+    output_path: PathBuf,
+    schema: Schema,
+    with_closure: bool,
+    phonetic: bool,
+    pos_codes: bool,
+    sql: String,
+    definition_count: i64,
+    word_count: i64,
+    word_definition_count: i64,
+    verb_sentence_ids: HashSet<u32>
+}
 
-    // Get words and definitions
-    let (definitions, words) = word_data;
+impl SqlWriter{
+    pub fn new(output_path: &Path, schema: Schema, with_closure: bool, phonetic: bool, pos_codes: bool) -> SqlWriter{
+        SqlWriter {
+            output_path: output_path.to_owned(),
+            schema,
+            with_closure,
+            phonetic,
+            pos_codes,
+            sql: String::new(),
+            definition_count: 0,
+            word_count: 0,
+            word_definition_count: 0,
+            verb_sentence_ids: HashSet::new()
+        }
+    }
 
-    // Create a hashmap to store offset and id values
-    let mut definition_ids: HashMap<u64, i64> = HashMap::new();
+    fn create_normalized_tables(&mut self){
+        self.sql.push_str(&format!("{};\n", CREATE_PART_OF_SPEECH_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_DEFINITION_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_DEFINITION_ILI_INDEX));
+        self.sql.push_str(&format!("{};\n", CREATE_WORD_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_PRONUNCIATION_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_PRONUNCIATION_RHYME_INDEX));
+        self.sql.push_str(&format!("{};\n", CREATE_WORD_DEFINITION_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_SYNSET_RELATION_TABLE));
+
+        for statement in CREATE_SYNSET_RELATION_INDEXES{
+            self.sql.push_str(&format!("{};\n", statement));
+        }
 
-    // Create an integer to store the IDs
-    let mut definition_id: i64 = 1;
-    let mut word_id: i64 = 1;
-    let mut word_definition_id: i64 = 1;
+        self.sql.push_str(&format!("{};\n", CREATE_SENSE_RELATION_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_SENSE_RELATION_INDEX));
+        self.sql.push_str(&format!("{};\n", CREATE_ADJECTIVE_CLUSTER_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_SATELLITE_ANTONYM_VIEW));
+        self.sql.push_str(&format!("{};\n", CREATE_VERB_FRAME_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_WORD_DEFINITION_VERB_FRAME_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_VERB_SENTENCE_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_WORD_DEFINITION_VERB_SENTENCE_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_TRANSLATION_TABLE));
+        self.sql.push_str(&format!("{};\n", CREATE_TRANSLATION_INDEX));
+
+        // Insert parts of speech
+        for part_of_speech in PartOfSpeech::ALL{
+            self.sql.push_str(&format!("INSERT INTO part_of_speech VALUES({},'{}');\n", part_of_speech.id(), part_of_speech.label(self.pos_codes)));
+        }
 
-    // Create string to store SQL
-    let mut sql: String = String::new();
+        // Insert verb frame templates
+        for (id, template) in (1..).zip(VERB_FRAMES){
+            self.sql.push_str(&format!("INSERT INTO verb_frame VALUES({},'{}');\n", id, escape(template)));
+        }
 
-    // Write create statements
-    sql.push_str("PRAGMA defer_foreign_keys=ON;\n");
+        if self.phonetic{
+            for statement in ADD_WORD_PHONETIC_COLUMNS.iter().chain(&CREATE_WORD_PHONETIC_INDEXES){
+                self.sql.push_str(&format!("{};\n", statement));
+            }
+        }
 
-    sql.push_str("BEGIN TRANSACTION;\n");
+        if self.with_closure{
+            self.sql.push_str(&format!("{};\n", CREATE_SYNSET_ANCESTOR_TABLE));
+            self.sql.push_str(&format!("{};\n", CREATE_SYNSET_ANCESTOR_INDEX));
+        }
+    }
+}
 
-    sql.push_str("CREATE TABLE definition (
-                            id              INTEGER PRIMARY KEY AUTOINCREMENT,
-                            data            TEXT,
-                            part_of_speech  TEXT NOT NULL
-    );\n");
+impl OutputWriter for SqlWriter{
+    fn label(&self) -> &str{
+        "SQL"
+    }
 
-    sql.push_str("CREATE TABLE word (
-                            id      INTEGER PRIMARY KEY AUTOINCREMENT,
-                            data    TEXT NOT NULL
-    );\n");
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating SQL...");
 
-    sql.push_str("CREATE TABLE word_definition (
-                            id              INTEGER PRIMARY KEY AUTOINCREMENT,
-                            definition_id   INTEGER,
-                            word_id         INTEGER,
-                            FOREIGN KEY (definition_id) REFERENCES definition(id),
-                            FOREIGN KEY (word_id) REFERENCES word(id)
-    );\n");
+        // Write create statements
+        self.sql.push_str("PRAGMA defer_foreign_keys=ON;\n");
+        self.sql.push_str("BEGIN TRANSACTION;\n");
 
-    // Process words and definitions
-    for (word, offsets) in words{
-        // Insert word
-        sql.push_str(&format!("INSERT INTO word VALUES({},'{}');\n", word_id, word));
+        match self.schema{
+            Schema::Normalized => self.create_normalized_tables(),
+            Schema::Flat => self.sql.push_str(&format!("{};\n", CREATE_FLAT_ENTRY_TABLE)),
+            Schema::Kv => self.sql.push_str(&format!("{};\n", CREATE_WORD_JSON_TABLE))
+        }
 
-        // Insert definitions
-        for offset in offsets{
-            if let Entry::Vacant(e) = definition_ids.entry(offset){
-                // Get definition
-                let definition_option = definitions.get(&offset);
+        Ok(())
+    }
+
+    fn write_synset(&mut self, id: i64, definition: &Definition) -> Result<()>{
+        // Definitions are written with their words in the other schemas
+        if self.schema != Schema::Normalized{
+            return Ok(());
+        }
 
-                if let Some(definition) = definition_option{
-                    // Insert definition
-                    sql.push_str(&format!("INSERT INTO definition VALUES({},'{}','{}');\n", definition_id, definition.data, definition.part_of_speech));
+        // Insert definition
+        self.sql.push_str(&format!("INSERT INTO definition VALUES({},'{}',{},{},{},{});\n", id, escape(&definition.data), definition.part_of_speech.id(),
+            text_or_null(definition.lexname.as_deref()), definition.depth.map_or("NULL".to_string(), |depth| depth.to_string()), text_or_null(definition.ili.as_deref())));
+        self.definition_count = self.definition_count.max(id);
 
-                    // Create an offset/id association
-                    e.insert(definition_id);
+        // Insert translations
+        for (lang, lemmas) in &definition.translations{
+            for lemma in lemmas{
+                self.sql.push_str(&format!("INSERT INTO translation VALUES({},'{}','{}');\n", id, escape(lang), escape(lemma)));
+            }
+        }
+
+        Ok(())
+    }
 
-                    // Increment definition id
-                    definition_id += 1;
+    fn write_word(&mut self, id: i64, word: &str, entry: &Word, senses: &[WordSense]) -> Result<()>{
+        match self.schema{
+            Schema::Normalized => {}
+            Schema::Flat => {
+                // Insert a row for every sense, numbered as in the index files
+                for sense in senses{
+                    self.sql.push_str(&format!("INSERT INTO entry VALUES('{}','{}',{},'{}',{});\n", escape(word), sense.definition.part_of_speech.label(self.pos_codes), sense.sense.sense_number,
+                        escape(&sense.definition.data), text_or_null(sense.definition.ili.as_deref())));
                 }
+
+                return Ok(());
+            }
+            Schema::Kv => {
+                // Insert the word as JSON
+                self.sql.push_str(&format!("INSERT INTO word_json VALUES('{}','{}');\n", escape(word), escape(&dictionary_handler::word_to_json(word, entry, senses, self.pos_codes)?)));
+
+                return Ok(());
             }
+        }
+
+        // Insert word
+        let phonetic_values: String = match (self.phonetic, &entry.phonetic){
+            (true, Some(keys)) => format!(",'{}','{}','{}'", escape(&keys.soundex), escape(&keys.metaphone), escape(&keys.metaphone_alternate)),
+            (true, None) => String::from(",NULL,NULL,NULL"),
+            (false, _) => String::new()
+        };
+        self.sql.push_str(&format!("INSERT INTO word VALUES({},'{}','{}',{},'{}',{}{});\n", id, escape(word), escape(&entry.display), entry.proper_noun as u8, escape(&entry.signature), entry.frequency, phonetic_values));
+        self.word_count = self.word_count.max(id);
+
+        // Insert pronunciations
+        for (variant, pronunciation) in entry.pronunciations.iter().enumerate(){
+            self.sql.push_str(&format!("INSERT INTO pronunciation VALUES({},{},'{}','{}',{},'{}');\n", id, variant, escape(&pronunciation.phonemes), escape(&pronunciation.stress), pronunciation.syllables, escape(&pronunciation.rhyme)));
+        }
+
+        // Add entries to associative table
+        for sense in senses{
+            self.sql.push_str(&format!("INSERT INTO word_definition VALUES({},{},{},{},'{}',{});\n", sense.id, sense.synset_id, id, sense.sense.frequency, escape(&sense.sense.form), text_or_null(sense.sense.marker.as_deref())));
+            self.word_definition_count = self.word_definition_count.max(sense.id);
 
-            // Add entry to associative table
-            if let Some(id) = definition_ids.get(&offset){
-                sql.push_str(&format!("INSERT INTO word_definition VALUES({},{},{});\n", word_definition_id, id, word_id));
-                word_definition_id += 1;
+            // Link the verb frames and example sentences of the sense
+            for frame in &sense.sense.frames{
+                self.sql.push_str(&format!("INSERT INTO word_definition_verb_frame VALUES({},{});\n", sense.id, frame));
             }
+
+            for (number, sentence) in &sense.sense.sentences{
+                if self.verb_sentence_ids.insert(*number){
+                    self.sql.push_str(&format!("INSERT INTO verb_sentence VALUES({},'{}');\n", number, escape(sentence)));
+                }
+
+                self.sql.push_str(&format!("INSERT INTO word_definition_verb_sentence VALUES({},{});\n", sense.id, number));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_relation(&mut self, relation: &Relation) -> Result<()>{
+        if self.schema != Schema::Normalized{
+            return Ok(());
+        }
+
+        // Insert semantic relation
+        self.sql.push_str(&format!("INSERT INTO synset_relation VALUES({},{},'{}');\n", relation.source_id, relation.target_id, relation.pointer.relation_name()));
+
+        Ok(())
+    }
+
+    fn write_sense_relation(&mut self, relation: &SenseRelation) -> Result<()>{
+        if self.schema != Schema::Normalized{
+            return Ok(());
         }
 
-        // Increment word id
-        word_id += 1;
+        // Insert lexical relation
+        self.sql.push_str(&format!("INSERT INTO sense_relation VALUES({},{},'{}');\n", relation.source_id, relation.target_id, relation.pointer.relation_name()));
+
+        Ok(())
     }
 
-    // Add extra necessary SQL after loop
-    sql.push_str("DELETE FROM sqlite_sequence;\n");
-    sql.push_str(&format!("INSERT INTO sqlite_sequence VALUES('definition',{});\n", definition_id - 1));
-    sql.push_str(&format!("INSERT INTO sqlite_sequence VALUES('word',{});\n", word_id - 1));
-    sql.push_str(&format!("INSERT INTO sqlite_sequence VALUES('word_definition',{});\n", word_definition_id - 1));
-    sql.push_str("COMMIT;");
+    fn write_adjective_cluster(&mut self, head_id: i64, satellite_id: i64) -> Result<()>{
+        if self.schema != Schema::Normalized{
+            return Ok(());
+        }
 
-    // Save to file
-    let mut file = File::create(output_path.join("dictionary_dump.sql"))?;
-    file.write_all(sql.as_bytes())?;
+        // Insert cluster membership
+        self.sql.push_str(&format!("INSERT INTO adjective_cluster VALUES({},{});\n", satellite_id, head_id));
+
+        Ok(())
+    }
+
+    fn write_ancestor(&mut self, synset_id: i64, ancestor_id: i64, depth: u32) -> Result<()>{
+        if self.schema != Schema::Normalized{
+            return Ok(());
+        }
+
+        // Insert ancestor
+        self.sql.push_str(&format!("INSERT INTO synset_ancestor VALUES({},{},{});\n", synset_id, ancestor_id, depth));
+
+        Ok(())
+    }
 
-    Ok(())
-}
\ No newline at end of file
+    fn finish(&mut self) -> Result<()>{
+        // Add extra necessary SQL after the inserts, as only the normalized schema has AUTOINCREMENT tables
+        if self.schema == Schema::Normalized{
+            self.sql.push_str("DELETE FROM sqlite_sequence;\n");
+            self.sql.push_str(&format!("INSERT INTO sqlite_sequence VALUES('definition',{});\n", self.definition_count));
+            self.sql.push_str(&format!("INSERT INTO sqlite_sequence VALUES('word',{});\n", self.word_count));
+            self.sql.push_str(&format!("INSERT INTO sqlite_sequence VALUES('word_definition',{});\n", self.word_definition_count));
+        }
+
+        self.sql.push_str("COMMIT;");
+
+        // Save to file
+        let mut file = File::create(self.output_path.join("dictionary_dump.sql"))?;
+        file.write_all(self.sql.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+
+/// Quote a value for a CSV field, doubling up on double quotes
+fn csv_field(value: &str) -> String{
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+
+/// Writes node and relationship CSV files with the headers neo4j-admin import expects,
+/// with lexical pointers (e.g. antonym) between Word nodes and the rest between Synset nodes
+pub struct Neo4jWriter{
+    output_path: PathBuf,
+    with_cypher: bool,
+    synset_ids: HashMap<i64, String>,
+    sense_lemmas: HashMap<i64, String>,
+    word_nodes: String,
+    synset_nodes: String,
+    sense_rels: String,
+    pointer_rels: String,
+    lexical_rels: String,
+    pointer_types: BTreeSet<String>,
+    lexical_types: BTreeSet<String>
+}
+
+impl Neo4jWriter{
+    pub fn new(output_path: &Path, with_cypher: bool) -> Neo4jWriter{
+        Neo4jWriter {
+            output_path: output_path.to_owned(),
+            with_cypher,
+            synset_ids: HashMap::new(),
+            sense_lemmas: HashMap::new(),
+            word_nodes: String::new(),
+            synset_nodes: String::new(),
+            sense_rels: String::new(),
+            pointer_rels: String::new(),
+            lexical_rels: String::new(),
+            pointer_types: BTreeSet::new(),
+            lexical_types: BTreeSet::new()
+        }
+    }
+
+    /// LOAD CSV script for the files, with a MERGE for each relationship type written since types can't come from a file
+    fn cypher(&self) -> String{
+        let mut cypher: String = String::from(NEO4J_CYPHER);
+
+        for relation_type in &self.pointer_types{
+            cypher.push_str(&format!("\nLOAD CSV WITH HEADERS FROM 'file:///rels_pointer.csv' AS row
+WITH row WHERE row.`:TYPE` = '{0}'
+MATCH (source:Synset {{synsetId: row.`:START_ID(Synset)`}}), (target:Synset {{synsetId: row.`:END_ID(Synset)`}})
+MERGE (source)-[:{0}]->(target);\n", relation_type));
+        }
+
+        for relation_type in &self.lexical_types{
+            cypher.push_str(&format!("\nLOAD CSV WITH HEADERS FROM 'file:///rels_lexical.csv' AS row
+WITH row WHERE row.`:TYPE` = '{0}'
+MATCH (source:Word {{lemma: row.`:START_ID(Word)`}}), (target:Word {{lemma: row.`:END_ID(Word)`}})
+MERGE (source)-[:{0} {{sourceSynsetId: row.sourceSynsetId, targetSynsetId: row.targetSynsetId}}]->(target);\n", relation_type));
+        }
+
+        cypher
+    }
+}
+
+impl OutputWriter for Neo4jWriter{
+    fn label(&self) -> &str{
+        "Neo4j CSV"
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating Neo4j CSV...");
+
+        // Write headers
+        self.word_nodes.push_str("lemma:ID(Word),display,signature,frequency:int,properNoun:boolean,:LABEL\n");
+        self.synset_nodes.push_str("synsetId:ID(Synset),definition,partOfSpeech,lexname,depth:int,ili,:LABEL\n");
+        self.sense_rels.push_str(":START_ID(Word),:END_ID(Synset),frequency:int,form,marker,:TYPE\n");
+        self.pointer_rels.push_str(":START_ID(Synset),:END_ID(Synset),:TYPE\n");
+        self.lexical_rels.push_str(":START_ID(Word),:END_ID(Word),sourceSynsetId,targetSynsetId,:TYPE\n");
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, id: i64, definition: &Definition) -> Result<()>{
+        let synset_id: String = definition.synset_id();
+
+        self.synset_nodes.push_str(&format!("{},{},{},{},{},{},Synset\n",
+            csv_field(&synset_id), csv_field(&definition.data), csv_field(definition.part_of_speech.name()),
+            definition.lexname.as_deref().map_or(String::new(), csv_field), definition.depth.map_or(String::new(), |depth| depth.to_string()),
+            definition.ili.as_deref().map_or(String::new(), csv_field)));
+
+        self.synset_ids.insert(id, synset_id);
+
+        Ok(())
+    }
+
+    fn write_word(&mut self, _id: i64, word: &str, entry: &Word, senses: &[WordSense]) -> Result<()>{
+        self.word_nodes.push_str(&format!("{},{},{},{},{},Word\n",
+            csv_field(word), csv_field(&entry.display), csv_field(&entry.signature), entry.frequency, entry.proper_noun));
+
+        for sense in senses{
+            self.sense_rels.push_str(&format!("{},{},{},{},{},SENSE\n",
+                csv_field(word), csv_field(&self.synset_ids[&sense.synset_id]), sense.sense.frequency,
+                csv_field(&sense.sense.form), sense.sense.marker.as_deref().map_or(String::new(), csv_field)));
+
+            // Remember the word of each sense for lexical relations
+            self.sense_lemmas.insert(sense.id, word.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn write_relation(&mut self, relation: &Relation) -> Result<()>{
+        let relation_type: String = relation.pointer.relation_name().to_uppercase();
+
+        self.pointer_rels.push_str(&format!("{},{},{}\n",
+            csv_field(&self.synset_ids[&relation.source_id]), csv_field(&self.synset_ids[&relation.target_id]), relation_type));
+        self.pointer_types.insert(relation_type);
+
+        Ok(())
+    }
+
+    fn write_sense_relation(&mut self, relation: &SenseRelation) -> Result<()>{
+        let relation_type: String = relation.pointer.relation_name().to_uppercase();
+
+        self.lexical_rels.push_str(&format!("{},{},{},{},{}\n",
+            csv_field(&self.sense_lemmas[&relation.source_id]), csv_field(&self.sense_lemmas[&relation.target_id]),
+            csv_field(&self.synset_ids[&relation.source_synset_id]), csv_field(&self.synset_ids[&relation.target_synset_id]), relation_type));
+        self.lexical_types.insert(relation_type);
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        // Save to files
+        let files: [(&str, &String); 5] = [
+            ("nodes_word.csv", &self.word_nodes),
+            ("nodes_synset.csv", &self.synset_nodes),
+            ("rels_sense.csv", &self.sense_rels),
+            ("rels_pointer.csv", &self.pointer_rels),
+            ("rels_lexical.csv", &self.lexical_rels)
+        ];
+
+        for (file_name, contents) in files{
+            let mut file = File::create(self.output_path.join(file_name))?;
+            file.write_all(contents.as_bytes())?;
+        }
+
+        // Save a script that loads the same files with LOAD CSV
+        if self.with_cypher{
+            let mut file = File::create(self.output_path.join("import.cypher"))?;
+            file.write_all(self.cypher().as_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+
+const NEO4J_CYPHER: &str = "// Bulk import into an empty database instead with:
+// neo4j-admin database import full --nodes=nodes_word.csv --nodes=nodes_synset.csv --relationships=rels_sense.csv --relationships=rels_pointer.csv --relationships=rels_lexical.csv
+// Otherwise copy the CSV files into the import directory and run this script
+
+CREATE CONSTRAINT word_lemma IF NOT EXISTS FOR (w:Word) REQUIRE w.lemma IS UNIQUE;
+CREATE CONSTRAINT synset_id IF NOT EXISTS FOR (s:Synset) REQUIRE s.synsetId IS UNIQUE;
+
+LOAD CSV WITH HEADERS FROM 'file:///nodes_word.csv' AS row
+CREATE (:Word {lemma: row.`lemma:ID(Word)`, display: row.display, signature: row.signature,
+    frequency: toInteger(row.`frequency:int`), properNoun: toBoolean(row.`properNoun:boolean`)});
+
+LOAD CSV WITH HEADERS FROM 'file:///nodes_synset.csv' AS row
+CREATE (:Synset {synsetId: row.`synsetId:ID(Synset)`, definition: row.definition, partOfSpeech: row.partOfSpeech,
+    lexname: row.lexname, depth: toInteger(row.`depth:int`), ili: row.ili});
+
+LOAD CSV WITH HEADERS FROM 'file:///rels_sense.csv' AS row
+MATCH (w:Word {lemma: row.`:START_ID(Word)`}), (s:Synset {synsetId: row.`:END_ID(Synset)`})
+CREATE (w)-[:SENSE {frequency: toInteger(row.`frequency:int`), form: row.form, marker: row.marker}]->(s);
+";
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::dictionary_handler::{Definitions, Sense, WordData, Words};
+    use crate::output_handler;
+    use regex::Regex;
+    use std::fs;
+
+    /// animal with dog as a hyponym
+    const DATA_LINES: [&str; 2] = [
+        "00000200 05 n 01 animal 0 001 ~ 00000300 n 0000 | a living organism",
+        "00000300 05 n 01 dog 0 001 @ 00000200 n 0000 | a domestic dog"
+    ];
+
+    fn sense(offset: u64, word: &str) -> Sense{
+        Sense {
+            synset: (PartOfSpeech::Noun, offset),
+            sense_number: 1,
+            sense_key: None,
+            frequency: 0,
+            form: word.to_string(),
+            marker: None,
+            frames: vec![],
+            sentences: vec![]
+        }
+    }
+
+    fn word_data() -> WordData{
+        let definition_regex: Regex = Regex::new(dictionary_handler::DEFINITION_PATTERN).unwrap();
+        let mut definitions: Definitions = Definitions::new();
+
+        for line in DATA_LINES{
+            let definition: Definition = dictionary_handler::parse_data_line(line, PartOfSpeech::Noun, &definition_regex).unwrap();
+            definitions.insert((PartOfSpeech::Noun, definition.offset), definition);
+        }
+
+        let mut words: Words = Words::new();
+        words.insert(String::from("animal"), Word { senses: vec![sense(200, "animal")], ..Default::default() });
+        words.insert(String::from("dog"), Word { senses: vec![sense(300, "dog")], ..Default::default() });
+
+        (definitions, words)
+    }
+
+    /// A directory of its own for each test
+    fn output_dir(test_name: &str) -> PathBuf{
+        let output_path: PathBuf = std::env::temp_dir().join(format!("wordnet2db_{}_{}", test_name, std::process::id()));
+        fs::create_dir_all(&output_path).unwrap();
+
+        output_path
+    }
+
+    /// Every synset relation as (source definition, relation, target definition)
+    fn synset_relations(conn: &Connection) -> Vec<(String, String, String)>{
+        conn.prepare("SELECT source.data, relation.relation, target.data FROM synset_relation relation
+                      JOIN definition source ON source.id = relation.source_id
+                      JOIN definition target ON target.id = relation.target_id
+                      ORDER BY relation.relation").unwrap()
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap()
+            .collect::<Result<Vec<(String, String, String)>, _>>().unwrap()
+    }
+
+    fn expected_relations() -> Vec<(String, String, String)>{
+        vec![
+            (String::from("a domestic dog"), String::from("hypernym"), String::from("a living organism")),
+            (String::from("a living organism"), String::from("hyponym"), String::from("a domestic dog"))
+        ]
+    }
+
+    #[test]
+    fn sqlite_synset_relations(){
+        let output_path: PathBuf = output_dir("sqlite_synset_relations");
+        let mut writer: SqliteWriter = SqliteWriter::new(&output_path, Schema::Normalized, false, false, false).unwrap();
+        output_handler::write_output(&mut writer, &word_data()).unwrap();
+
+        let conn: Connection = Connection::open(output_path.join("dictionary.sqlite3")).unwrap();
+        let relations: Vec<(String, String, String)> = synset_relations(&conn);
+        fs::remove_dir_all(&output_path).unwrap();
+
+        assert_eq!(relations, expected_relations());
+    }
+
+    #[test]
+    fn sql_synset_relations(){
+        let output_path: PathBuf = output_dir("sql_synset_relations");
+        output_handler::write_output(&mut SqlWriter::new(&output_path, Schema::Normalized, false, false, false), &word_data()).unwrap();
+
+        let sql: String = fs::read_to_string(output_path.join("dictionary_dump.sql")).unwrap();
+        fs::remove_dir_all(&output_path).unwrap();
+
+        let conn: Connection = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&sql).unwrap();
+
+        assert_eq!(synset_relations(&conn), expected_relations());
+    }
+
+    #[test]
+    fn flat_entries_carry_ili(){
+        let output_path: PathBuf = output_dir("flat_entries_carry_ili");
+        let mut word_data: WordData = word_data();
+        word_data.0.get_mut(&(PartOfSpeech::Noun, 300)).unwrap().ili = Some(String::from("i46360"));
+
+        let mut writer: SqliteWriter = SqliteWriter::new(&output_path, Schema::Flat, false, false, false).unwrap();
+        output_handler::write_output(&mut writer, &word_data).unwrap();
+
+        let conn: Connection = Connection::open(output_path.join("dictionary.sqlite3")).unwrap();
+        let entries: Vec<(String, Option<String>)> = conn.prepare("SELECT word, ili FROM entry ORDER BY word").unwrap()
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+            .collect::<Result<Vec<(String, Option<String>)>, _>>().unwrap();
+        fs::remove_dir_all(&output_path).unwrap();
+
+        assert_eq!(entries, [(String::from("animal"), None), (String::from("dog"), Some(String::from("i46360")))]);
+    }
+}
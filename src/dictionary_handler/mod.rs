@@ -1,27 +1,283 @@
-use anyhow::Result;
-use crate::Args;
-use crate::file_handler::IndexDataPair;
+use anyhow::{anyhow, bail, Result};
+use crate::{Args, LengthExclude, PartOfSpeech};
+use crate::file_handler::{self, IndexDataPair};
+use crate::output_handler::{OutputWriter, WordSense};
+use crate::phonetic_handler::PhoneticKeys;
+use crate::pronunciation_handler::Pronunciation;
+use colored::Colorize;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use serde::Serialize;
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
-pub type Definitions = HashMap<u64, Definition>;
-pub type Words = BTreeMap<String, HashSet<u64>>;
+/// A synset is identified by the part of speech of its data file and its byte offset in it
+pub type SynsetKey = (PartOfSpeech, u64);
+pub type Definitions = HashMap<SynsetKey, Definition>;
+pub type Words = BTreeMap<String, Word>;
 pub type WordData = (Definitions, Words);
+/// Lemmas of a synset in other languages, keyed by language code
+pub type Translations = BTreeMap<String, Vec<String>>;
+/// Regex for getting the definition from a data file line
+pub(crate) const DEFINITION_PATTERN: &str = r"\|\s{1}(?<definition>[^;]+[^\s;]+)";
 
-#[derive(Serialize, Deserialize)]
-struct WordDataJson{
-    word: String,
-    definitions: Vec<Definition>
+/// Sentence templates of the generic verb frames, numbered from 1 in data files
+pub const VERB_FRAMES: [&str; 35] = [
+    "Something ----s",
+    "Somebody ----s",
+    "It is ----ing",
+    "Something is ----ing PP",
+    "Something ----s something Adjective/Noun",
+    "Something ----s Adjective/Noun",
+    "Somebody ----s Adjective",
+    "Somebody ----s something",
+    "Somebody ----s somebody",
+    "Something ----s somebody",
+    "Something ----s something",
+    "Something ----s to somebody",
+    "Somebody ----s on something",
+    "Somebody ----s somebody something",
+    "Somebody ----s something to somebody",
+    "Somebody ----s something from somebody",
+    "Somebody ----s somebody with something",
+    "Somebody ----s somebody of something",
+    "Somebody ----s something on somebody",
+    "Somebody ----s somebody PP",
+    "Somebody ----s something PP",
+    "Somebody ----s PP",
+    "Somebody's (body part) ----s",
+    "Somebody ----s somebody to INFINITIVE",
+    "Somebody ----s somebody INFINITIVE",
+    "Somebody ----s that CLAUSE",
+    "Somebody ----s to somebody",
+    "Somebody ----s to INFINITIVE",
+    "Somebody ----s whether INFINITIVE",
+    "Somebody ----s somebody into V-ing something",
+    "Somebody ----s something with something",
+    "Somebody ----s INFINITIVE",
+    "Somebody ----s VERB-ing",
+    "It ----s that CLAUSE",
+    "Something ----s INFINITIVE"
+];
+
+/// Synsets above a synset through hypernym pointers, with the fewest links needed to reach each
+pub type Ancestors = Vec<(SynsetKey, u32)>;
+
+#[derive(Serialize)]
+struct WordDataJson<'a>{
+    word: &'a str,
+    display: &'a str,
+    proper_noun: bool,
+    signature: &'a str,
+    frequency: u32,
+    #[serde(flatten)]
+    phonetic: Option<&'a PhoneticKeys>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pronunciations: &'a [Pronunciation],
+    definitions: Vec<DefinitionJson<'a>>
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
+struct DefinitionJson<'a>{
+    part_of_speech: &'static str,
+    #[serde(flatten)]
+    definition: &'a Definition,
+    frequency: u32,
+    form: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    marker: Option<&'a str>,
+    /// Synset ID of the head adjective a satellite is clustered around
+    #[serde(skip_serializing_if = "Option::is_none")]
+    head: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    frames: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sentences: Vec<String>
+}
+
+#[derive(Clone, Serialize)]
 pub struct Definition{
     pub data: String,
-    pub part_of_speech: String
+    /// Written by each writer, as a code or a full name
+    #[serde(skip)]
+    pub part_of_speech: PartOfSpeech,
+    /// Name of the lexicographer file the synset comes from (e.g. noun.animal)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lexname: Option<String>,
+    /// Fewest hypernym links between the synset and the top of its hierarchy, when the closure was computed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
+    #[serde(skip)]
+    pub offset: u64,
+    #[serde(skip)]
+    pub lex_file: u8,
+    /// Synset type from the data file (n, v, a, s or r)
+    #[serde(skip)]
+    pub synset_type: char,
+    #[serde(skip)]
+    pub words: Vec<SynsetWord>,
+    #[serde(skip)]
+    pub pointers: Vec<Pointer>,
+    /// Generic frames of a verb synset
+    #[serde(skip)]
+    pub frames: Vec<VerbFrame>,
+    /// Every synset above this one through hypernym pointers, with the fewest links needed to reach it
+    #[serde(skip)]
+    pub ancestors: Ancestors,
+    /// Lemmas of the synset in other languages from Open Multilingual Wordnet, keyed by language code (e.g. fra)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: Translations,
+    /// Interlingual Index identifier of the synset (e.g. i35545), shared with other wordnets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ili: Option<String>
+}
+
+/// A word as it appears on a data file line
+#[derive(Clone)]
+pub struct SynsetWord{
+    pub data: String,
+    pub lex_id: u8,
+    /// Adjective syntactic marker: p (predicate), a (prenominal) or ip (immediately postnominal)
+    pub marker: Option<String>
+}
+
+/// A generic sentence frame a verb synset's words can be used in
+#[derive(Clone)]
+pub struct VerbFrame{
+    /// Number of the frame in VERB_FRAMES, starting from 1
+    pub number: u8,
+    /// Word number in the synset the frame applies to, 0 when it applies to every word
+    pub word_number: u8
+}
+
+#[derive(Clone)]
+pub struct Pointer{
+    pub symbol: String,
+    pub target: SynsetKey,
+    /// Word number in the source synset, 0 when the pointer is between whole synsets
+    pub source_word: u8,
+    /// Word number in the target synset, 0 when the pointer is between whole synsets
+    pub target_word: u8
+}
+
+impl Definition{
+    /// Identifier of the synset in offset-pos form (e.g. 02084071-n), as used by the Open Multilingual Wordnet
+    pub fn synset_id(&self) -> String{
+        format_synset_id(self.part_of_speech, self.offset)
+    }
+
+    /// Whether the synset is an adjective satellite, clustered around a head synset
+    pub fn is_satellite(&self) -> bool{
+        self.synset_type == 's'
+    }
+
+    /// Key of the head synset a satellite adjective is similar to
+    pub fn satellite_head(&self) -> Option<&SynsetKey>{
+        if !self.is_satellite(){
+            return None;
+        }
+
+        self.pointers.iter()
+            .find(|pointer| pointer.symbol == "&")
+            .map(|pointer| &pointer.target)
+    }
+
+    /// Numbers of the verb frames that apply to a word of the synset, in the form used by the index files
+    pub fn word_frames(&self, lemma: &str) -> Vec<u8>{
+        let word_number: Option<u8> = self.words.iter().position(|word| base_lemma(&word.data) == lemma).map(|index| index as u8 + 1);
+
+        let mut numbers: Vec<u8> = self.frames.iter()
+            .filter(|frame| frame.word_number == 0 || Some(frame.word_number) == word_number)
+            .map(|frame| frame.number)
+            .collect();
+
+        // A frame can be listed for the whole synset and for the word
+        numbers.sort_unstable();
+        numbers.dedup();
+
+        numbers
+    }
+
+    /// Words of the synset with spaces in place of underscores
+    pub fn display_words(&self) -> Vec<String>{
+        self.words.iter().map(|word| word.data.replace('_', " ")).collect()
+    }
+}
+
+impl Pointer{
+    /// Name of the relation the pointer symbol stands for
+    pub fn relation_name(&self) -> &str{
+        match self.symbol.as_str(){
+            "!" => "antonym",
+            "@" => "hypernym",
+            "@i" => "instance_hypernym",
+            "~" => "hyponym",
+            "~i" => "instance_hyponym",
+            "#m" => "member_holonym",
+            "#s" => "substance_holonym",
+            "#p" => "part_holonym",
+            "%m" => "member_meronym",
+            "%s" => "substance_meronym",
+            "%p" => "part_meronym",
+            "=" => "attribute",
+            "+" => "derivation",
+            ";c" => "domain_topic",
+            "-c" => "member_topic",
+            ";r" => "domain_region",
+            "-r" => "member_region",
+            ";u" => "domain_usage",
+            "-u" => "member_usage",
+            "*" => "entailment",
+            ">" => "cause",
+            "^" => "also_see",
+            "$" => "verb_group",
+            "&" => "similar_to",
+            "<" => "participle",
+            "\\" => "pertainym",
+            _ => "other"
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Word{
+    /// Senses of the word, in the order the index files list them
+    pub senses: Vec<Sense>,
+    /// Human readable form of the word with spaces in place of underscores (e.g. ice cream for ice_cream)
+    pub display: String,
+    /// Whether every sense of the word is a capitalized noun (e.g. Einstein)
+    pub proper_noun: bool,
+    /// Letters of the word in sorted order, shared by all of its anagrams
+    pub signature: String,
+    /// Total tag count of the word's senses
+    pub frequency: u32,
+    /// Sound-alike keys of the word, when requested
+    pub phonetic: Option<PhoneticKeys>,
+    /// Pronunciations of the word from the CMU Pronouncing Dictionary, with any alternates after the first
+    pub pronunciations: Vec<Pronunciation>
+}
+
+/// A word's membership in a synset
+#[derive(Clone)]
+pub struct Sense{
+    pub synset: SynsetKey,
+    /// Position of the sense on the word's index line, which is WordNet's sense number within the part of speech
+    pub sense_number: u32,
+    /// Sense key identifying the word in the synset (e.g. dog%1:05:00::), if the word was found on the data line
+    pub sense_key: Option<String>,
+    /// Number of times the sense was tagged in the semantic concordance texts
+    pub frequency: u32,
+    /// Word as written in the synset, keeping its case (e.g. Paris for paris)
+    pub form: String,
+    /// Adjective syntactic marker of the word in the synset
+    pub marker: Option<String>,
+    /// Numbers of the verb frames the word can be used in with this sense
+    pub frames: Vec<u8>,
+    /// Numbered example sentences of a verb sense from sents.vrb, with %s in place of the verb
+    pub sentences: Vec<(u32, String)>
 }
 
 pub fn get_word_data(index_data_pairs: &Vec<IndexDataPair>, args: &Args) -> Result<WordData>{
@@ -33,7 +289,7 @@ pub fn get_word_data(index_data_pairs: &Vec<IndexDataPair>, args: &Args) -> Resu
     let mut words: Words = Words::new();
 
     // Create a regex for getting the definition
-    let definition_regex: Regex = Regex::new(r"\|\s{1}(?<definition>[^;]+[^\s;]+)")?;
+    let definition_regex: Regex = Regex::new(DEFINITION_PATTERN)?;
 
     // Create a regex to detect any number
     let number_regex: Regex = Regex::new(r"\d")?;
@@ -44,14 +300,51 @@ pub fn get_word_data(index_data_pairs: &Vec<IndexDataPair>, args: &Args) -> Resu
     // Create a regex to detect whole words
     let whole_regex: Regex = Regex::new(r"[[:punct:]]|\s")?;
 
+    // Get the characters of the alphabet and create a regex for the crossword pattern
+    let alphabet: Option<HashSet<char>> = args.alphabet.as_deref().map(alphabet_characters);
+    let pattern_regex: Option<Regex> = args.pattern.as_ref().map(|pattern| Regex::new(&pattern_to_regex(pattern))).transpose()?;
+
+    // Get the signature words must have to be an anagram
+    let anagram_signature: Option<String> = args.anagram_of.as_deref().map(letter_signature);
+
+    // Read any word lists
+    let wordlist: Option<HashSet<String>> = args.wordlist.as_deref().map(file_handler::read_word_list).transpose()?;
+    let stoplist: Option<HashSet<String>> = args.stoplist.as_deref().map(file_handler::read_word_list).transpose()?;
+
+    // Read any pronunciations
+    let cmudict: HashMap<String, Vec<String>> = args.cmudict.as_deref().map(file_handler::read_cmudict).transpose()?.unwrap_or_default();
+
+    // Read the lexicographer file names
+    let lexnames: HashMap<u8, String> = file_handler::read_lexnames(&args.directory)?;
+
+    // Throw an error for any lexicographer file name filters that don't exist
+    for lexname in &args.lexname{
+        if !lexnames.values().any(|name| name == lexname){
+            bail!(format!("Unknown lexicographer file {}!", lexname).red())
+        }
+    }
+
+    // Read the sense tag counts
+    let tag_counts: HashMap<String, u32> = file_handler::read_tag_counts(&args.directory)?;
+
+    // Read the example sentences of verb senses
+    let verb_sentences: HashMap<String, Vec<(u32, String)>> = file_handler::read_verb_sentences(&args.directory)?;
+
+    // Throw an error if frequency filters are used without tag counts
+    if tag_counts.is_empty() && (args.min_frequency.is_some() || args.top_n.is_some()){
+        bail!("Frequency filters need the cntlist.rev or cntlist file!".red())
+    }
+
     // Loop through files and update dictionary
     for pair in index_data_pairs{
+        // Skip files for parts of speech that weren't requested
+        if !args.pos.is_empty() && !args.pos.contains(&pair.get_part_of_speech()){
+            continue;
+        }
+
         // Get a reader for reading the lines from the data
         let mut data_reader: BufReader<File> = BufReader::new(File::open(pair.get_data_path())?);
 
-        // Create a string to hold the current line of the data file
-        let mut data_current_line: String = String::new();
-
         // Create a reader for reading the lines from the index
         let mut index_reader: BufReader<File> = BufReader::new(File::open(pair.get_index_path())?);
 
@@ -66,8 +359,8 @@ pub fn get_word_data(index_data_pairs: &Vec<IndexDataPair>, args: &Args) -> Resu
                 continue;
             }
 
-            // Get the word (double up on single quotes if present)
-            let found_word: String = index_line.split(' ').collect::<Vec<&str>>()[0].to_string().replace("'", "''");
+            // Get the word in NFC form
+            let found_word: String = index_line.split(' ').collect::<Vec<&str>>()[0].nfc().collect();
 
             // Check for numbers
             if !args.keep_numbers && number_regex.is_match(&found_word){
@@ -81,10 +374,33 @@ pub fn get_word_data(index_data_pairs: &Vec<IndexDataPair>, args: &Args) -> Resu
                 continue;
             }
 
+            // Check against the word lists
+            if wordlist.as_ref().is_some_and(|list| !list.contains(&found_word)) || stoplist.as_ref().is_some_and(|list| list.contains(&found_word)){
+                index_line.clear();
+                continue;
+            }
+
+            // Check against the word regexes
+            if (!args.include_word.is_empty() && !args.include_word.iter().any(|regex| regex.is_match(&found_word)))
+                || args.exclude_word.iter().any(|regex| regex.is_match(&found_word)){
+                index_line.clear();
+                continue;
+            }
+
+            // Check against the word game filters
+            if alphabet.as_ref().is_some_and(|alphabet| !found_word.chars().all(|character| alphabet.contains(&character)))
+                || pattern_regex.as_ref().is_some_and(|regex| !regex.is_match(&found_word))
+                || args.require_letters.as_ref().is_some_and(|letters| !contains_letters(&found_word, letters))
+                || args.forbid_letters.as_ref().is_some_and(|letters| letters.to_lowercase().chars().any(|letter| found_word.contains(letter)))
+                || anagram_signature.as_ref().is_some_and(|signature| *signature != letter_signature(&found_word)){
+                index_line.clear();
+                continue;
+            }
+
             // Check word length against arguments
-            let word_length: usize = found_word.len();
+            let word_length: usize = word_length(&found_word, &args.length_excludes);
 
-            if args.char_counts.len() > 0{
+            if !args.char_counts.is_empty(){
                 if !args.char_counts.contains(&word_length){
                     index_line.clear();
                     continue;
@@ -99,76 +415,659 @@ pub fn get_word_data(index_data_pairs: &Vec<IndexDataPair>, args: &Args) -> Resu
                 .filter_map(|captures| captures["offset"].parse::<u64>().ok())
                 .collect();
 
-            // Modify if present
-            if let Some(entry) = words.get_mut(&found_word){
-                for offset in byte_offsets.clone(){
-                    entry.insert(offset);
-                }
-            } else{
-                // Add it otherwise
-                words.insert(found_word, HashSet::from_iter(byte_offsets.clone()));
-            }
+            // Create a vector for the senses the word is kept in
+            let mut kept_senses: Vec<Sense> = vec![];
 
             // Loop over vector and add them to the HashMap if not present
-            for offset in byte_offsets{
-                // Seek to the byte offset in the data file
-                data_reader.seek(SeekFrom::Start(offset))?;
+            for (sense_number, offset) in (1..).zip(byte_offsets){
+                let key: SynsetKey = (pair.get_part_of_speech(), offset);
 
-                // Read the line at the offset
-                data_reader.read_line(&mut data_current_line)?;
+                // Skip senses whose synset can't be parsed
+                if let Entry::Vacant(e) = definitions.entry(key){
+                    let Some(definition) = read_definition(&mut data_reader, &key, &definition_regex, &lexnames)? else{
+                        continue;
+                    };
 
-               // Create an empty definition string
-               let mut definition: String = String::new();
+                    e.insert(definition);
+                }
 
-               // Get any definition that was found (double up on single quotes if found)
-               if let Some(captures) = definition_regex.captures(&data_current_line){
-                   definition = captures["definition"].to_string().replace("'", "''");
-               }
+                // Check the definition against the definition regexes
+                if args.exclude_definition.iter().any(|regex| regex.is_match(&definitions[&key].data)){
+                    continue;
+                }
 
-               // Add it to the definitions if it wasn't present
-               definitions.entry(offset).or_insert_with(|| Definition { data: definition, part_of_speech: pair.get_part_of_speech().clone() });
+                // Check the definition's lexicographer file
+                if !args.lexname.is_empty() && !definitions[&key].lexname.as_ref().is_some_and(|lexname| args.lexname.contains(lexname)){
+                    continue;
+                }
 
-               // Clear current line
-               data_current_line.clear();
-           }
+                // Load the head synset of satellite adjectives, which is part of their sense keys
+                let head_key: Option<SynsetKey> = definitions[&key].satellite_head().cloned();
+
+                if let Some(head_key) = &head_key{
+                    if let Entry::Vacant(e) = definitions.entry(*head_key){
+                        if let Some(definition) = read_definition(&mut data_reader, head_key, &definition_regex, &lexnames)?{
+                            e.insert(definition);
+                        }
+                    }
+                }
+
+                // Get the sense key and its tag count
+                let sense_key: Option<String> = get_sense_key(&found_word, &definitions[&key], head_key.as_ref().and_then(|head_key| definitions.get(head_key)));
+                let frequency: u32 = sense_key.as_ref().and_then(|sense_key| tag_counts.get(sense_key)).copied().unwrap_or(0);
+
+                // Check the sense's tag count
+                if args.min_frequency.is_some_and(|min_frequency| frequency < min_frequency){
+                    continue;
+                }
+
+                // Get the case preserved form and any adjective marker of the word in the synset
+                let synset_word: Option<&SynsetWord> = definitions[&key].words.iter().find(|word| base_lemma(&word.data) == found_word);
+                let form: String = synset_word.map_or_else(|| found_word.clone(), |word| word.data.clone());
+                let marker: Option<String> = synset_word.and_then(|word| word.marker.clone());
+
+                // Get the verb frames and example sentences of the sense
+                let frames: Vec<u8> = definitions[&key].word_frames(&found_word);
+                let sentences: Vec<(u32, String)> = sense_key.as_ref().and_then(|sense_key| verb_sentences.get(sense_key)).cloned().unwrap_or_default();
+
+                kept_senses.push(Sense { synset: key, sense_number, sense_key, frequency, form, marker, frames, sentences });
+            }
+
+            // Add the word if any of its synsets were kept, modifying it if present
+            if !kept_senses.is_empty(){
+                let display: String = found_word.replace('_', " ");
+                let signature: String = letter_signature(&found_word);
+                let phonetic: Option<PhoneticKeys> = args.phonetic.then(|| PhoneticKeys::new(&found_word));
+                let pronunciations: Vec<Pronunciation> = cmudict.get(&found_word).into_iter().flatten().map(|phonemes| Pronunciation::new(phonemes)).collect();
+                let entry: &mut Word = words.entry(found_word).or_insert_with(|| Word { display, signature, phonetic, pronunciations, ..Default::default() });
+
+                for sense in kept_senses{
+                    if !entry.senses.iter().any(|existing| existing.synset == sense.synset){
+                        entry.frequency += sense.frequency;
+                        entry.senses.push(sense);
+                    }
+                }
+            }
            // Clear current line
            index_line.clear();
         }
+
+        // Load every hypernym above the loaded synsets so their ancestors can be found
+        if args.with_closure{
+            let mut pending: Vec<SynsetKey> = definitions.keys().filter(|key| key.0 == pair.get_part_of_speech()).cloned().collect();
+
+            while let Some(key) = pending.pop(){
+                let targets: Vec<SynsetKey> = hypernyms(&definitions[&key]).cloned().collect();
+
+                for target in targets{
+                    if let Entry::Vacant(e) = definitions.entry(target){
+                        if let Some(definition) = read_definition(&mut data_reader, &target, &definition_regex, &lexnames)?{
+                            e.insert(definition);
+                            pending.push(target);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Find the ancestors and depth of every synset
+    if args.with_closure{
+        add_closure(&mut definitions);
+    }
+
+    // Add the translations of every synset
+    if !args.omw.is_empty(){
+        let mut translations: HashMap<SynsetKey, Translations> = file_handler::read_omw(&args.omw)?;
+
+        for (key, definition) in definitions.iter_mut(){
+            if let Some(languages) = translations.remove(key){
+                definition.translations = languages;
+            }
+        }
+    }
+
+    // Add the interlingual identifier of every synset
+    if let Some(ili_path) = &args.ili{
+        let ili_ids: HashMap<SynsetKey, String> = file_handler::read_ili(ili_path)?;
+
+        for (key, definition) in definitions.iter_mut(){
+            definition.ili = ili_ids.get(key).cloned();
+        }
+    }
+
+    // Flag words that are always capitalized nouns, and use their capitalization for display if requested
+    for entry in words.values_mut(){
+        entry.proper_noun = entry.senses.iter().all(|sense| sense.synset.0 == PartOfSpeech::Noun && sense.form.starts_with(char::is_uppercase));
+
+        if args.preserve_case{
+            if let Some(sense) = entry.senses.first(){
+                entry.display = sense.form.replace('_', " ");
+            }
+        }
+    }
+
+    // Keep only the most frequent words if requested, breaking ties alphabetically
+    if let Some(top_n) = args.top_n{
+        let mut ranked: Vec<(&String, u32)> = words.iter().map(|(word, entry)| (word, entry.frequency)).collect();
+        ranked.sort_by_key(|(_, frequency)| std::cmp::Reverse(*frequency));
+
+        let kept: HashSet<String> = ranked.into_iter().take(top_n).map(|(word, _)| word.clone()).collect();
+        words.retain(|word, _| kept.contains(word));
     }
 
     Ok((definitions, words))
 }
 
 
-pub fn word_data_to_json(output_path: &Path, word_data: WordData) -> Result<()>{
+
+/// Read every synset from the data files, with the ancestors and depth of each
+pub fn read_all_definitions(index_data_pairs: &Vec<IndexDataPair>, dir_path: &Path) -> Result<Definitions>{
     // Print status message
-    println!("Creating JSON...");
-
-    // Get words and definitions
-    let (definitions, words) = word_data;
-    
-    // Create vec to store words/definitions
-    let mut data_vec: Vec<WordDataJson> = vec![];
-
-    // Process words and definitions
-    for (word, offsets) in words{
-        // Create a WordDataJson object to work on
-        let mut word_json: WordDataJson = WordDataJson{word, definitions: vec![]};
-
-        // Get every definition for the word
-        for offset in offsets{
-            if let Some(definition) = definitions.get(&offset){
-                word_json.definitions.push(Definition{data: definition.data.clone(), part_of_speech: definition.part_of_speech.clone()});
+    println!("Reading synsets...");
+
+    // Create definition collection
+    let mut definitions: Definitions = Definitions::new();
+
+    // Create a regex for getting the definition
+    let definition_regex: Regex = Regex::new(DEFINITION_PATTERN)?;
+
+    // Read the lexicographer file names
+    let lexnames: HashMap<u8, String> = file_handler::read_lexnames(dir_path)?;
+
+    for pair in index_data_pairs{
+        // Create a reader for reading the lines from the data
+        let mut data_reader: BufReader<File> = BufReader::new(File::open(pair.get_data_path())?);
+
+        // Create a string to store current line
+        let mut data_line: String = String::new();
+
+        // Loop until EOF in data
+        while data_reader.read_line(&mut data_line)? != 0{
+            // Skip license lines (start with two spaces)
+            if !data_line.starts_with("  "){
+                // Skip lines that can't be parsed
+                let mut definition: Definition = match parse_data_line(&data_line, pair.get_part_of_speech(), &definition_regex){
+                    Ok(definition) => definition,
+                    Err(error) => {
+                        println!("{} {}", format!("Skipping synset {}:", data_line.split_whitespace().next().unwrap_or_default()).yellow(), error);
+                        data_line.clear();
+                        continue;
+                    }
+                };
+
+                definition.lexname = lexnames.get(&definition.lex_file).cloned();
+
+                definitions.insert((pair.get_part_of_speech(), definition.offset), definition);
+            }
+
+            // Clear current line
+            data_line.clear();
+        }
+    }
+
+    add_closure(&mut definitions);
+
+    Ok(definitions)
+}
+
+
+/// Number of grapheme clusters in a word, leaving out any excluded characters
+pub fn word_length(word: &str, excludes: &[LengthExclude]) -> usize{
+    word.graphemes(true)
+        .filter(|grapheme| !match *grapheme{
+            "_" | " " => excludes.contains(&LengthExclude::Spaces),
+            "-" | "\u{2010}" => excludes.contains(&LengthExclude::Hyphens),
+            "'" | "\u{2019}" => excludes.contains(&LengthExclude::Apostrophes),
+            _ => false
+        })
+        .count()
+}
+
+
+/// Letters of a word, lowercased and sorted, ignoring anything that isn't a letter
+pub fn letter_signature(word: &str) -> String{
+    let mut letters: Vec<char> = word.chars()
+        .filter(|character| character.is_alphabetic())
+        .flat_map(|character| character.to_lowercase())
+        .collect();
+
+    letters.sort_unstable();
+
+    letters.into_iter().collect()
+}
+
+
+/// Characters of an alphabet, lowercased. A hyphen between two characters is a range (e.g. a-z), and anything else is taken literally
+fn alphabet_characters(alphabet: &str) -> HashSet<char>{
+    let characters: Vec<char> = alphabet.to_lowercase().chars().collect();
+    let mut alphabet: HashSet<char> = HashSet::new();
+    let mut index: usize = 0;
+
+    while index < characters.len(){
+        if characters.get(index + 1) == Some(&'-') && index + 2 < characters.len(){
+            alphabet.extend(characters[index]..=characters[index + 2]);
+            index += 3;
+        } else{
+            alphabet.insert(characters[index]);
+            index += 1;
+        }
+    }
+
+    alphabet
+}
+
+
+/// Whether a word has every letter, as many times as it's repeated (e.g. ee needs two e's)
+fn contains_letters(word: &str, letters: &str) -> bool{
+    let letters: String = letters.to_lowercase();
+
+    letters.chars().all(|letter| word.matches(letter).count() >= letters.matches(letter).count())
+}
+
+
+/// Turn a crossword style pattern into an anchored regex, with ? matching any single character
+fn pattern_to_regex(pattern: &str) -> String{
+    let body: String = pattern.to_lowercase().chars()
+        .map(|character| if character == '?' { ".".to_string() } else { regex::escape(&character.to_string()) })
+        .collect();
+
+    format!("^{}$", body)
+}
+
+
+/// Seek to a synset in its data file and parse it, or warn and return None if its line is malformed
+fn read_definition(data_reader: &mut BufReader<File>, key: &SynsetKey, definition_regex: &Regex, lexnames: &HashMap<u8, String>) -> Result<Option<Definition>>{
+    // Create a string to hold the line
+    let mut data_line: String = String::new();
+
+    // Seek to the byte offset in the data file
+    data_reader.seek(SeekFrom::Start(key.1))?;
+
+    // Read the line at the offset
+    data_reader.read_line(&mut data_line)?;
+
+    // Parse the line, skipping it if it's malformed
+    let mut definition: Definition = match parse_data_line(&data_line, key.0, definition_regex){
+        Ok(definition) => definition,
+        Err(error) => {
+            println!("{} {}", format!("Skipping synset {:08}:", key.1).yellow(), error);
+            return Ok(None);
+        }
+    };
+
+    // Name its lexicographer file
+    definition.lexname = lexnames.get(&definition.lex_file).cloned();
+
+    Ok(Some(definition))
+}
+
+
+/// Targets of a synset's hypernym and instance hypernym pointers
+pub fn hypernyms(definition: &Definition) -> impl Iterator<Item = &SynsetKey>{
+    definition.pointers.iter()
+        .filter(|pointer| pointer.symbol == "@" || pointer.symbol == "@i")
+        .map(|pointer| &pointer.target)
+}
+
+
+/// Set the ancestors and depth of every synset by walking up the hypernyms breadth first
+pub fn add_closure(definitions: &mut Definitions){
+    let mut closures: Vec<(SynsetKey, Ancestors, Option<u32>)> = vec![];
+
+    for key in definitions.keys(){
+        let mut ancestors: Ancestors = vec![];
+        let mut depth: Option<u32> = None;
+        let mut seen: HashSet<&SynsetKey> = HashSet::from([key]);
+        let mut frontier: Vec<&SynsetKey> = vec![key];
+        let mut distance: u32 = 0;
+
+        while !frontier.is_empty(){
+            let mut next: Vec<&SynsetKey> = vec![];
+
+            for current in frontier{
+                let Some(definition) = definitions.get(current) else{
+                    continue;
+                };
+
+                // Synsets without hypernyms are the top of a hierarchy
+                let mut has_hypernym: bool = false;
+
+                for target in hypernyms(definition){
+                    has_hypernym = true;
+
+                    if seen.insert(target){
+                        ancestors.push((*target, distance + 1));
+                        next.push(target);
+                    }
+                }
+
+                if !has_hypernym && depth.is_none(){
+                    depth = Some(distance);
+                }
             }
+
+            frontier = next;
+            distance += 1;
         }
 
-        data_vec.push(word_json);
+        closures.push((*key, ancestors, depth));
+    }
+
+    for (key, ancestors, depth) in closures{
+        if let Some(definition) = definitions.get_mut(&key){
+            definition.ancestors = ancestors;
+            definition.depth = depth;
+        }
     }
+}
+
+
+/// Lowercase a data file word so it matches the index files
+pub fn base_lemma(word: &str) -> String{
+    word.to_lowercase()
+}
+
+
+/// Put a word typed by a user in the form used by the index files (e.g. Ice Cream to ice_cream)
+pub fn normalize_word(word: &str) -> String{
+    word.trim().to_lowercase().replace(' ', "_").nfc().collect()
+}
+
+
+/// Identifier of a synset in offset-pos form. Satellites share the a of their heads, as in the Open Multilingual Wordnet
+pub fn format_synset_id(part_of_speech: PartOfSpeech, offset: u64) -> String{
+    format!("{:08}-{}", offset, part_of_speech.data_file().code())
+}
+
+
+/// Build the sense key (lemma%ss_type:lex_filenum:lex_id:head_word:head_id) of a word in a synset
+pub fn get_sense_key(lemma: &str, definition: &Definition, head: Option<&Definition>) -> Option<String>{
+    // Get the lexical id of the word in the synset
+    let lex_id: u8 = definition.words.iter().find(|word| base_lemma(&word.data) == lemma)?.lex_id;
+
+    // Synset types are numbered in sense keys
+    let synset_type: u8 = match definition.synset_type{
+        'n' => 1,
+        'v' => 2,
+        'a' => 3,
+        'r' => 4,
+        's' => 5,
+        _ => return None
+    };
+
+    // Satellites name the first word of their head synset
+    let head_part: String = match head.and_then(|head| head.words.first()){
+        Some(head_word) => format!("{}:{:02}", base_lemma(&head_word.data), head_word.lex_id),
+        None => ":".to_string()
+    };
+
+    Some(format!("{}%{}:{:02}:{:02}:{}", lemma, synset_type, definition.lex_file, lex_id, head_part))
+}
+
+
+pub(crate) fn parse_data_line(line: &str, part_of_speech: PartOfSpeech, definition_regex: &Regex) -> Result<Definition>{
+    // Create an error for lines that end early
+    let malformed = || anyhow!(format!("Malformed data line: {}", line.trim()).red());
+
+    // Split the synset fields from the gloss
+    let (fields, _) = line.split_once('|').unwrap_or((line, ""));
+    let mut fields = fields.split_whitespace();
+
+    // Get the byte offset, lexicographer file number and synset type
+    let offset: u64 = fields.next().ok_or_else(malformed)?.parse()?;
+    let lex_file: u8 = fields.next().ok_or_else(malformed)?.parse()?;
+    let synset_type: char = fields.next().ok_or_else(malformed)?.chars().next().ok_or_else(malformed)?;
+
+    // Get the words (hexadecimal count followed by word/lex_id pairs)
+    let word_count: usize = usize::from_str_radix(fields.next().ok_or_else(malformed)?, 16)?;
+    let mut words: Vec<SynsetWord> = Vec::with_capacity(word_count);
+
+    for _ in 0..word_count{
+        let mut data: String = fields.next().ok_or_else(malformed)?.nfc().collect();
+        let lex_id: u8 = u8::from_str_radix(fields.next().ok_or_else(malformed)?, 16)?;
+
+        // Split off any adjective marker e.g. afraid(p)
+        let mut marker: Option<String> = None;
+
+        if let Some((word, marker_text)) = data.strip_suffix(')').and_then(|word| word.split_once('(')){
+            marker = Some(marker_text.to_string());
+            data = word.to_string();
+        }
 
-    // Save to file
-    let json: String = serde_json::to_string_pretty(&data_vec)?;
-    let mut file = File::create(output_path.join("dictionary.json"))?;
-    file.write_all(json.as_bytes())?;
-    
-    Ok(())
-}
\ No newline at end of file
+        words.push(SynsetWord { data, lex_id, marker });
+    }
+
+    // Get the pointers (decimal count followed by symbol/offset/part of speech/source-target groups)
+    let pointer_count: usize = fields.next().ok_or_else(malformed)?.parse()?;
+    let mut pointers: Vec<Pointer> = Vec::with_capacity(pointer_count);
+
+    for _ in 0..pointer_count{
+        let symbol: String = fields.next().ok_or_else(malformed)?.to_string();
+        let target_offset: u64 = fields.next().ok_or_else(malformed)?.parse()?;
+        let target_part_of_speech: PartOfSpeech = PartOfSpeech::from_code(fields.next().ok_or_else(malformed)?).ok_or_else(malformed)?.data_file();
+        let source_target: u16 = u16::from_str_radix(fields.next().ok_or_else(malformed)?, 16)?;
+
+        pointers.push(Pointer {
+            symbol,
+            target: (target_part_of_speech, target_offset),
+            source_word: (source_target >> 8) as u8,
+            target_word: (source_target & 0xff) as u8
+        });
+    }
+
+    // Get the verb frames (decimal count followed by + frame number/word number groups)
+    let mut frames: Vec<VerbFrame> = vec![];
+
+    if synset_type == 'v'{
+        let frame_count: usize = fields.next().ok_or_else(malformed)?.parse()?;
+
+        for _ in 0..frame_count{
+            fields.next().filter(|plus| *plus == "+").ok_or_else(malformed)?;
+            let number: u8 = fields.next().ok_or_else(malformed)?.parse()?;
+            let word_number: u8 = u8::from_str_radix(fields.next().ok_or_else(malformed)?, 16)?;
+
+            frames.push(VerbFrame { number, word_number });
+        }
+    }
+
+    // Create an empty definition string
+    let mut definition: String = String::new();
+
+    // Get any definition that was found in NFC form
+    if let Some(captures) = definition_regex.captures(line){
+        definition = captures["definition"].nfc().collect();
+    }
+
+    // Satellites are reported apart from the head adjectives they cluster around
+    let part_of_speech: PartOfSpeech = match synset_type{
+        's' => PartOfSpeech::AdjectiveSatellite,
+        _ => part_of_speech
+    };
+
+    Ok(Definition {
+        data: definition,
+        part_of_speech,
+        lexname: None,
+        depth: None,
+        offset,
+        lex_file,
+        synset_type,
+        words,
+        pointers,
+        frames,
+        ancestors: vec![],
+        translations: Translations::new(),
+        ili: None
+    })
+}
+
+
+/// A word with every definition, as written to JSON output
+fn word_data_json<'a>(word: &'a str, entry: &'a Word, senses: &[WordSense<'a>], pos_codes: bool) -> WordDataJson<'a>{
+    WordDataJson {
+        word,
+        display: &entry.display,
+        proper_noun: entry.proper_noun,
+        signature: &entry.signature,
+        frequency: entry.frequency,
+        phonetic: entry.phonetic.as_ref(),
+        pronunciations: &entry.pronunciations,
+        definitions: senses.iter().map(|sense| DefinitionJson {
+            part_of_speech: sense.definition.part_of_speech.label(pos_codes),
+            definition: sense.definition,
+            frequency: sense.sense.frequency,
+            form: &sense.sense.form,
+            marker: sense.sense.marker.as_deref(),
+            head: sense.definition.satellite_head().map(|head| format_synset_id(head.0, head.1)),
+            frames: sense.sense.frames.iter().filter_map(|number| (*number as usize).checked_sub(1).and_then(|index| VERB_FRAMES.get(index)).copied()).collect(),
+            sentences: sense.sense.sentences.iter().map(|(_, sentence)| sentence.replace("%s", &entry.display)).collect()
+        }).collect()
+    }
+}
+
+
+/// Serialize a word with every definition to the same JSON object JSON output uses
+pub fn word_to_json(word: &str, entry: &Word, senses: &[WordSense], pos_codes: bool) -> Result<String>{
+    Ok(serde_json::to_string(&word_data_json(word, entry, senses, pos_codes))?)
+}
+
+
+/// Writes every word as an element of a JSON array.
+///
+/// Words are serialized as they're visited, so only their JSON is held until the file is written.
+pub struct JsonWriter{
+    output_path: PathBuf,
+    pos_codes: bool,
+    data_vec: Vec<String>
+}
+
+impl JsonWriter{
+    pub fn new(output_path: &Path, pos_codes: bool) -> JsonWriter{
+        JsonWriter { output_path: output_path.to_owned(), pos_codes, data_vec: vec![] }
+    }
+}
+
+impl OutputWriter for JsonWriter{
+    fn label(&self) -> &str{
+        "JSON"
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating JSON...");
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, _id: i64, _definition: &Definition) -> Result<()>{
+        Ok(())
+    }
+
+    fn write_word(&mut self, _id: i64, word: &str, entry: &Word, senses: &[WordSense]) -> Result<()>{
+        // Get every definition for the word, indented as an element of the array
+        let json: String = serde_json::to_string_pretty(&word_data_json(word, entry, senses, self.pos_codes))?;
+        self.data_vec.push(json.lines().map(|line| format!("  {}", line)).collect::<Vec<String>>().join("\n"));
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        // Save to file, laid out as a pretty printed array would be
+        let json: String = match self.data_vec.is_empty(){
+            true => String::from("[]"),
+            false => format!("[\n{}\n]", self.data_vec.join(",\n"))
+        };
+        let mut file = File::create(self.output_path.join("dictionary.json"))?;
+        file.write_all(json.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn definition(line: &str, part_of_speech: PartOfSpeech) -> Definition{
+        parse_data_line(line, part_of_speech, &Regex::new(DEFINITION_PATTERN).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn lengths_count_grapheme_clusters(){
+        // e followed by a combining acute accent is one grapheme cluster
+        assert_eq!(word_length("cafe\u{301}", &[]), 4);
+        assert_eq!(word_length("caf\u{e9}", &[]), 4);
+        assert_eq!(word_length("ice_cream", &[]), 9);
+        assert_eq!(word_length("ice_cream", &[LengthExclude::Spaces]), 8);
+        assert_eq!(word_length("jack-o'-lantern", &[LengthExclude::Hyphens]), 13);
+        assert_eq!(word_length("jack-o'-lantern", &[LengthExclude::Hyphens, LengthExclude::Apostrophes]), 12);
+    }
+
+    #[test]
+    fn alphabets_take_ranges_and_literals(){
+        assert_eq!(alphabet_characters("a-c"), HashSet::from(['a', 'b', 'c']));
+        assert_eq!(alphabet_characters("A-C_"), HashSet::from(['a', 'b', 'c', '_']));
+        assert_eq!(alphabet_characters("-]^\\"), HashSet::from(['-', ']', '^', '\\']));
+        assert_eq!(alphabet_characters("ab-"), HashSet::from(['a', 'b', '-']));
+        assert!(alphabet_characters("z-a").is_empty());
+    }
+
+    #[test]
+    fn required_letters_count_repeats(){
+        assert!(contains_letters("tree", "ET"));
+        assert!(contains_letters("tree", "ee"));
+        assert!(!contains_letters("ten", "ee"));
+        assert!(!contains_letters("tree", "eee"));
+    }
+
+    #[test]
+    fn patterns_match_one_character_per_question_mark(){
+        let regex: Regex = Regex::new(&pattern_to_regex("C?T")).unwrap();
+
+        assert_eq!(pattern_to_regex("c?t"), "^c.t$");
+        assert!(regex.is_match("cat"));
+        assert!(regex.is_match("cot"));
+        assert!(!regex.is_match("cast"));
+        assert!(!regex.is_match("ct"));
+
+        // Everything else is matched literally
+        assert_eq!(pattern_to_regex("a.b*"), "^a\\.b\\*$");
+        assert!(Regex::new(&pattern_to_regex("st.?")).unwrap().is_match("st.s"));
+        assert!(!Regex::new(&pattern_to_regex("st.?")).unwrap().is_match("stas"));
+    }
+
+    #[test]
+    fn noun_and_verb_sense_keys(){
+        let dog: Definition = definition("02084071 05 n 03 dog 0 domestic_dog 0 Canis_familiaris 0 000 | a member of the genus Canis", PartOfSpeech::Noun);
+        let paris: Definition = definition("08932568 15 n 03 Paris 0 City_of_Light 0 French_capital 0 000 | the capital and largest city of France", PartOfSpeech::Noun);
+        let run: Definition = definition("01926311 38 v 01 run 0 000 00 | move fast by using one's feet", PartOfSpeech::Verb);
+
+        assert_eq!(get_sense_key("dog", &dog, None).as_deref(), Some("dog%1:05:00::"));
+        assert_eq!(get_sense_key("canis_familiaris", &dog, None).as_deref(), Some("canis_familiaris%1:05:00::"));
+        assert_eq!(get_sense_key("paris", &paris, None).as_deref(), Some("paris%1:15:00::"));
+        assert_eq!(get_sense_key("run", &run, None).as_deref(), Some("run%2:38:00::"));
+        assert_eq!(get_sense_key("cat", &dog, None), None);
+    }
+
+    #[test]
+    fn lex_ids_are_hexadecimal_in_data_files(){
+        let bank: Definition = definition("09213565 17 n 01 bank b 000 | sloping land beside a body of water", PartOfSpeech::Noun);
+
+        assert_eq!(get_sense_key("bank", &bank, None).as_deref(), Some("bank%1:17:11::"));
+    }
+
+    #[test]
+    fn satellite_sense_keys_name_their_head(){
+        let dry: Definition = definition("01074242 00 a 01 dry 1 001 & 01074650 a 0000 | free from liquid or moisture", PartOfSpeech::Adjective);
+        let arid: Definition = definition("01074650 00 s 02 arid 0 waterless 0 001 & 01074242 a 0000 | lacking sufficient water or rainfall", PartOfSpeech::Adjective);
+        let afraid: Definition = definition("00077645 00 a 01 afraid(p) 0 000 | filled with fear or apprehension", PartOfSpeech::Adjective);
+
+        assert_eq!(arid.satellite_head(), Some(&(PartOfSpeech::Adjective, 1074242)));
+        assert_eq!(get_sense_key("arid", &arid, Some(&dry)).as_deref(), Some("arid%5:00:00:dry:01"));
+        assert_eq!(get_sense_key("waterless", &arid, Some(&dry)).as_deref(), Some("waterless%5:00:00:dry:01"));
+        assert_eq!(get_sense_key("dry", &dry, None).as_deref(), Some("dry%3:00:01::"));
+        assert_eq!(get_sense_key("afraid", &afraid, None).as_deref(), Some("afraid%3:00:00::"));
+    }
+}
@@ -1,16 +1,19 @@
 use anyhow::{bail, Result};
+use crate::PartOfSpeech;
+use crate::dictionary_handler::{self, SynsetKey, Translations};
 use colored::Colorize;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 pub struct IndexDataPair{
     index_path: PathBuf,
     data_path: PathBuf,
-    part_of_speech: String
+    part_of_speech: PartOfSpeech
 }
 
 impl IndexDataPair{
-    pub fn new(index_path: PathBuf, data_path: PathBuf, part_of_speech: String) -> IndexDataPair{
+    pub fn new(index_path: PathBuf, data_path: PathBuf, part_of_speech: PartOfSpeech) -> IndexDataPair{
         IndexDataPair { index_path, data_path, part_of_speech }
     }
 
@@ -22,8 +25,8 @@ impl IndexDataPair{
         &self.index_path
     }
 
-    pub fn get_part_of_speech(&self) -> &String{
-        &self.part_of_speech
+    pub fn get_part_of_speech(&self) -> PartOfSpeech{
+        self.part_of_speech
     }
 }
 
@@ -71,19 +74,11 @@ pub fn get_paths(dir_path: &Path) -> Result<Vec<IndexDataPair>>{
     for index_path in &index_paths{
         for data_path in &data_paths{
             if data_path.extension() == index_path.extension(){
-                 // Get part of speech
-                 let mut part_of_speech: String = String::new();
-
-                 if let Some(extension) = index_path.extension(){
-                     part_of_speech = extension.to_string_lossy().to_string();
-                 }
- 
-                 if part_of_speech == "adj"{
-                     part_of_speech = "adjective".to_string();
-                 } else if part_of_speech == "adv" {
-                     part_of_speech = "adverb".to_string();
-                 }
- 
+                 // Get part of speech, skipping files that aren't named after one
+                 let Some(part_of_speech) = index_path.extension().and_then(|extension| PartOfSpeech::from_extension(&extension.to_string_lossy())) else{
+                     continue;
+                 };
+
                  index_data_vec.push(IndexDataPair::new(index_path.to_owned(), data_path.to_owned(), part_of_speech));
                  continue;
             }
@@ -94,6 +89,253 @@ pub fn get_paths(dir_path: &Path) -> Result<Vec<IndexDataPair>>{
 }
 
 
+/// Read the lexicographer file names from the lexnames file, keyed by file number. Empty if the file isn't present
+pub fn read_lexnames(dir_path: &Path) -> Result<HashMap<u8, String>>{
+    // Create a hashmap for the names
+    let mut lexnames: HashMap<u8, String> = HashMap::new();
+
+    // Return nothing if the file isn't there
+    let lexnames_path: PathBuf = dir_path.join("lexnames");
+
+    if !lexnames_path.is_file(){
+        return Ok(lexnames);
+    }
+
+    // Lines are the file number, name, and syntactic category separated by tabs
+    for line in fs::read_to_string(lexnames_path)?.lines(){
+        let mut fields = line.split_whitespace();
+
+        if let (Some(number), Some(name)) = (fields.next(), fields.next()){
+            lexnames.insert(number.parse()?, name.to_string());
+        }
+    }
+
+    Ok(lexnames)
+}
+
+
+/// Read how many times each sense was tagged, keyed by sense key. Uses cntlist.rev, falling back to cntlist. Empty if neither is present
+pub fn read_tag_counts(dir_path: &Path) -> Result<HashMap<String, u32>>{
+    // Create a hashmap for the counts
+    let mut tag_counts: HashMap<String, u32> = HashMap::new();
+
+    // Lines are the sense key, sense number and tag count in cntlist.rev
+    let reversed_path: PathBuf = dir_path.join("cntlist.rev");
+
+    if reversed_path.is_file(){
+        for line in fs::read_to_string(reversed_path)?.lines(){
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            if let [sense_key, _, count] = fields[..]{
+                tag_counts.insert(sense_key.to_string(), count.parse()?);
+            }
+        }
+
+        return Ok(tag_counts);
+    }
+
+    // Lines are the tag count, sense key and sense number in cntlist
+    let count_path: PathBuf = dir_path.join("cntlist");
+
+    if count_path.is_file(){
+        for line in fs::read_to_string(count_path)?.lines(){
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            if let [count, sense_key, _] = fields[..]{
+                tag_counts.insert(sense_key.to_string(), count.parse()?);
+            }
+        }
+    }
+
+    Ok(tag_counts)
+}
+
+
+/// Read the example sentences of each verb sense from sents.vrb and sentidx.vrb, keyed by sense key. Empty if either isn't present
+pub fn read_verb_sentences(dir_path: &Path) -> Result<HashMap<String, Vec<(u32, String)>>>{
+    // Create a hashmap for the sentences
+    let mut verb_sentences: HashMap<String, Vec<(u32, String)>> = HashMap::new();
+
+    // Return nothing if either file isn't there
+    let sentences_path: PathBuf = dir_path.join("sents.vrb");
+    let index_path: PathBuf = dir_path.join("sentidx.vrb");
+
+    if !sentences_path.is_file() || !index_path.is_file(){
+        return Ok(verb_sentences);
+    }
+
+    // Lines are the sentence number followed by the sentence, with %s in place of the verb
+    let mut sentences: HashMap<u32, String> = HashMap::new();
+
+    for line in fs::read_to_string(sentences_path)?.lines(){
+        if let Some((number, sentence)) = line.split_once(' '){
+            sentences.insert(number.parse()?, sentence.trim().to_string());
+        }
+    }
+
+    // Lines are the sense key followed by a comma separated list of sentence numbers
+    for line in fs::read_to_string(index_path)?.lines(){
+        if let Some((sense_key, numbers)) = line.split_once(' '){
+            for number in numbers.trim().split(','){
+                let number: u32 = number.parse()?;
+
+                if let Some(sentence) = sentences.get(&number){
+                    verb_sentences.entry(sense_key.to_string()).or_default().push((number, sentence.clone()));
+                }
+            }
+        }
+    }
+
+    Ok(verb_sentences)
+}
+
+
+/// Read the base forms of irregular inflections from the exception lists (noun.exc, verb.exc, etc), keyed by inflected form
+pub fn read_exceptions(dir_path: &Path) -> Result<HashMap<String, Vec<String>>>{
+    // Create a hashmap for the base forms
+    let mut exceptions: HashMap<String, Vec<String>> = HashMap::new();
+
+    for extension in ["noun", "verb", "adj", "adv"]{
+        let exception_path: PathBuf = dir_path.join(format!("{}.exc", extension));
+
+        if !exception_path.is_file(){
+            continue;
+        }
+
+        // Lines are the inflected form followed by one or more base forms
+        for line in fs::read_to_string(exception_path)?.lines(){
+            let mut fields = line.split_whitespace();
+
+            if let Some(form) = fields.next(){
+                exceptions.entry(form.to_string()).or_default().extend(fields.map(|base| base.to_string()));
+            }
+        }
+    }
+
+    Ok(exceptions)
+}
+
+
+/// Read the pronunciations of each word in the CMU Pronouncing Dictionary, keyed by lowercase word
+pub fn read_cmudict(cmudict_path: &Path) -> Result<HashMap<String, Vec<String>>>{
+    // Throw an error if the dictionary can't be read. It isn't all UTF-8, so decode it lossily
+    let Ok(contents) = fs::read(cmudict_path) else{
+        bail!(format!("Unable to read {}", cmudict_path.to_string_lossy()).red())
+    };
+
+    // Create a hashmap for the pronunciations
+    let mut pronunciations: HashMap<String, Vec<String>> = HashMap::new();
+
+    // Lines are the word, with a number in brackets for alternate pronunciations (e.g. TOMATO(1)), followed by its phonemes
+    for line in String::from_utf8_lossy(&contents).lines(){
+        if line.starts_with(";;;"){
+            continue;
+        }
+
+        if let Some((word, phonemes)) = line.split_once(' '){
+            let word: &str = word.split_once('(').map_or(word, |(word, _)| word);
+            pronunciations.entry(word.to_lowercase()).or_default().push(phonemes.trim().to_string());
+        }
+    }
+
+    Ok(pronunciations)
+}
+
+
+/// Read the Interlingual Index identifier of each synset (e.g. i35545), keyed by synset
+pub fn read_ili(ili_path: &Path) -> Result<HashMap<SynsetKey, String>>{
+    // Throw an error if the mapping can't be read
+    let Ok(contents) = fs::read_to_string(ili_path) else{
+        bail!(format!("Unable to read {}", ili_path.to_string_lossy()).red())
+    };
+
+    // Create a hashmap for the identifiers
+    let mut ili_ids: HashMap<SynsetKey, String> = HashMap::new();
+
+    // Lines are the identifier followed by the synset as offset-pos
+    for line in contents.lines(){
+        let Some((ili_id, synset)) = line.split_once('\t') else{
+            continue;
+        };
+
+        let Some((offset, code)) = synset.trim().split_once('-') else{
+            continue;
+        };
+
+        if let (Some(part_of_speech), Ok(offset)) = (PartOfSpeech::from_code(code), offset.parse()){
+            ili_ids.insert((part_of_speech.data_file(), offset), ili_id.trim().to_string());
+        }
+    }
+
+    Ok(ili_ids)
+}
+
+
+/// Read the lemmas of each synset from Open Multilingual Wordnet tab files, keyed by synset and then language
+pub fn read_omw(omw_paths: &[PathBuf]) -> Result<HashMap<SynsetKey, Translations>>{
+    // Create a hashmap for the translations
+    let mut translations: HashMap<SynsetKey, Translations> = HashMap::new();
+
+    for omw_path in omw_paths{
+        // Throw an error if a file can't be read
+        let Ok(contents) = fs::read_to_string(omw_path) else{
+            bail!(format!("Unable to read {}", omw_path.to_string_lossy()).red())
+        };
+
+        // Lines are the synset as offset-pos, the language and type of the entry (e.g. fra:lemma), and the entry
+        for line in contents.lines().filter(|line| !line.starts_with('#')){
+            let mut fields = line.split('\t');
+
+            let (Some(synset), Some(kind), Some(lemma)) = (fields.next(), fields.next(), fields.next()) else{
+                continue;
+            };
+
+            // Skip definitions and examples
+            let Some(lang) = kind.strip_suffix(":lemma") else{
+                continue;
+            };
+
+            // Satellites are listed under s, but live in the adjective data file
+            let Some((offset, code)) = synset.split_once('-') else{
+                continue;
+            };
+
+            let Some(part_of_speech) = PartOfSpeech::from_code(code) else{
+                continue;
+            };
+
+            let Ok(offset) = offset.parse() else{
+                continue;
+            };
+
+            let lemmas: &mut Vec<String> = translations.entry((part_of_speech.data_file(), offset)).or_default().entry(lang.to_string()).or_default();
+
+            if !lemmas.iter().any(|existing| existing == lemma.trim()){
+                lemmas.push(lemma.trim().to_string());
+            }
+        }
+    }
+
+    Ok(translations)
+}
+
+
+/// Read a list of words, one per line, in the form used by the index files (lowercase with underscores for spaces)
+pub fn read_word_list(list_path: &Path) -> Result<HashSet<String>>{
+    // Throw an error if the list can't be read
+    let Ok(contents) = fs::read_to_string(list_path) else{
+        bail!(format!("Unable to read {}", list_path.to_string_lossy()).red())
+    };
+
+    // Skip blank lines and comments, and normalize the rest like index words
+    Ok(contents.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(dictionary_handler::normalize_word)
+        .collect())
+}
+
+
 pub fn is_valid_dir(dir_path: &Path) -> Result<bool>{
     // Check if directory exists. Needs to have multiple error messages in case existence can't be confirmed or denied
     if dir_path.try_exists().is_err(){
@@ -108,4 +350,117 @@ pub fn is_valid_dir(dir_path: &Path) -> Result<bool>{
     }
 
     Ok(true)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    /// Create an empty directory of its own for each test
+    fn test_dir(test_name: &str) -> PathBuf{
+        let dir_path: PathBuf = std::env::temp_dir().join(format!("wordnet2db_{}_{}", test_name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir_path);
+        fs::create_dir_all(&dir_path).unwrap();
+
+        dir_path
+    }
+
+    #[test]
+    fn reads_cntlist_rev(){
+        let dir_path: PathBuf = test_dir("cntlist_rev");
+        fs::write(dir_path.join("cntlist.rev"), "dog%1:05:00:: 1 42\narid%5:00:00:dry:01 1 3\nmalformed line\n").unwrap();
+
+        let tag_counts: HashMap<String, u32> = read_tag_counts(&dir_path).unwrap();
+        fs::remove_dir_all(&dir_path).unwrap();
+
+        assert_eq!(tag_counts.len(), 2);
+        assert_eq!(tag_counts["dog%1:05:00::"], 42);
+        assert_eq!(tag_counts["arid%5:00:00:dry:01"], 3);
+    }
+
+    #[test]
+    fn reads_cntlist(){
+        let dir_path: PathBuf = test_dir("cntlist");
+        fs::write(dir_path.join("cntlist"), "42 dog%1:05:00:: 1\n7 run%2:38:00:: 1\n").unwrap();
+
+        let tag_counts: HashMap<String, u32> = read_tag_counts(&dir_path).unwrap();
+        fs::remove_dir_all(&dir_path).unwrap();
+
+        assert_eq!(tag_counts["dog%1:05:00::"], 42);
+        assert_eq!(tag_counts["run%2:38:00::"], 7);
+    }
+
+    #[test]
+    fn missing_counts_are_empty(){
+        let dir_path: PathBuf = test_dir("no_cntlist");
+
+        let tag_counts: HashMap<String, u32> = read_tag_counts(&dir_path).unwrap();
+        fs::remove_dir_all(&dir_path).unwrap();
+
+        assert!(tag_counts.is_empty());
+    }
+
+    #[test]
+    fn reads_cmudict(){
+        let dir_path: PathBuf = test_dir("cmudict");
+        let cmudict_path: PathBuf = dir_path.join("cmudict-0.7b");
+        fs::write(&cmudict_path, ";;; # CMUdict  --  Major Version: 0.07\nTOMATO  T AH0 M EY1 T OW2\nTOMATO(1)  T AH0 M AA1 T OW2\nCAT  K AE1 T\n").unwrap();
+
+        let pronunciations: HashMap<String, Vec<String>> = read_cmudict(&cmudict_path).unwrap();
+        fs::remove_dir_all(&dir_path).unwrap();
+
+        assert_eq!(pronunciations.len(), 2);
+        assert_eq!(pronunciations["tomato"], ["T AH0 M EY1 T OW2", "T AH0 M AA1 T OW2"]);
+        assert_eq!(pronunciations["cat"], ["K AE1 T"]);
+    }
+
+    #[test]
+    fn reads_omw(){
+        let dir_path: PathBuf = test_dir("omw");
+        let fra_path: PathBuf = dir_path.join("wn-data-fra.tab");
+        let jpn_path: PathBuf = dir_path.join("wn-data-jpn.tab");
+        fs::write(&fra_path, "# Wordnet Libre du Français\tfra\thttp://alpage.inria.fr/~sagot/wolf.html\tCeCILL-C\n\
+            02084071-n\tfra:lemma\tchien\n\
+            02084071-n\tfra:lemma\tchien\n\
+            02084071-n\tfra:def\tun membre du genre Canis\n\
+            00000344-s\tfra:lemma\taride\n\
+            0000x255-a\tfra:lemma\tfroid\n").unwrap();
+        fs::write(&jpn_path, "02084071-n\tjpn:lemma\t犬\n").unwrap();
+
+        let translations: HashMap<SynsetKey, Translations> = read_omw(&[fra_path, jpn_path]).unwrap();
+        fs::remove_dir_all(&dir_path).unwrap();
+
+        let dog: &Translations = &translations[&(PartOfSpeech::Noun, 2084071)];
+
+        assert_eq!(translations.len(), 2);
+        assert_eq!(dog["fra"], ["chien"]);
+        assert_eq!(dog["jpn"], ["犬"]);
+        assert_eq!(translations[&(PartOfSpeech::Adjective, 344)]["fra"], ["aride"]);
+    }
+
+    #[test]
+    fn reads_ili(){
+        let dir_path: PathBuf = test_dir("ili");
+        let ili_path: PathBuf = dir_path.join("ili-map-pwn30.tab");
+        fs::write(&ili_path, "i35545\t02084071-n\ni2\t00000344-s\ni3\t0000x255-a\nmalformed line\n").unwrap();
+
+        let ili_ids: HashMap<SynsetKey, String> = read_ili(&ili_path).unwrap();
+        fs::remove_dir_all(&dir_path).unwrap();
+
+        assert_eq!(ili_ids.len(), 2);
+        assert_eq!(ili_ids[&(PartOfSpeech::Noun, 2084071)], "i35545");
+        assert_eq!(ili_ids[&(PartOfSpeech::Adjective, 344)], "i2");
+    }
+
+    #[test]
+    fn reads_word_list(){
+        let dir_path: PathBuf = test_dir("word_list");
+        let list_path: PathBuf = dir_path.join("wordlist.txt");
+        fs::write(&list_path, "# Words to keep\n\nCafe\u{301}\n  Ice Cream  \ndog\n").unwrap();
+
+        let words: HashSet<String> = read_word_list(&list_path).unwrap();
+        fs::remove_dir_all(&dir_path).unwrap();
+
+        assert_eq!(words, HashSet::from([String::from("caf\u{e9}"), String::from("ice_cream"), String::from("dog")]));
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::Result;
+use crate::PartOfSpeech;
+use crate::dictionary_handler::{Definition, Word};
+use crate::output_handler::{OutputWriter, WordSense};
+use fst::automaton::{Levenshtein, Str};
+use fst::{Automaton, IntoStreamer, Map, MapBuilder};
+use regex_automata::dense::{self, DenseDFA};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the file holding the fst map of words to entry IDs
+const MAP_FILE: &str = "dictionary.fst";
+/// Name of the file holding the definitions of every entry
+const DEFINITIONS_FILE: &str = "dictionary_definitions.bin";
+
+
+/// Writes an fst map from each word to its entry ID, with the definitions of each entry in a separate blob.
+///
+/// The blob starts with the entry count and the byte offset of every entry (plus one for the end), all as
+/// little endian u32s. Each entry is a line per sense of the part of speech code, the ILI identifier (empty
+/// if there is none) and the definition, separated by tabs.
+pub struct FstWriter{
+    output_path: PathBuf,
+    map_builder: MapBuilder<Vec<u8>>,
+    offsets: Vec<u32>,
+    entries: Vec<u8>
+}
+
+impl FstWriter{
+    pub fn new(output_path: &Path) -> FstWriter{
+        FstWriter { output_path: output_path.to_owned(), map_builder: MapBuilder::memory(), offsets: vec![], entries: vec![] }
+    }
+}
+
+impl OutputWriter for FstWriter{
+    fn label(&self) -> &str{
+        "FST"
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating FST...");
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, _id: i64, _definition: &Definition) -> Result<()>{
+        Ok(())
+    }
+
+    fn write_word(&mut self, _id: i64, word: &str, _entry: &Word, senses: &[WordSense]) -> Result<()>{
+        // Words arrive in byte order, which is the order the map has to be built in
+        self.map_builder.insert(word, self.offsets.len() as u64)?;
+        self.offsets.push(self.entries.len() as u32);
+
+        for sense in senses{
+            self.entries.extend_from_slice(format!("{}\t{}\t{}\n", sense.definition.part_of_speech.code(), sense.definition.ili.as_deref().unwrap_or_default(), sense.definition.data).as_bytes());
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        // Take the finished map
+        let map_bytes: Vec<u8> = std::mem::replace(&mut self.map_builder, MapBuilder::memory()).into_inner()?;
+
+        // Write the header of entry offsets before the entries
+        let mut blob: Vec<u8> = Vec::with_capacity(4 * (self.offsets.len() + 2) + self.entries.len());
+        blob.extend_from_slice(&(self.offsets.len() as u32).to_le_bytes());
+
+        for offset in self.offsets.iter().chain([&(self.entries.len() as u32)]){
+            blob.extend_from_slice(&offset.to_le_bytes());
+        }
+
+        blob.extend_from_slice(&self.entries);
+
+        // Save to files
+        let mut map_file = File::create(self.output_path.join(MAP_FILE))?;
+        map_file.write_all(&map_bytes)?;
+
+        let mut definitions_file = File::create(self.output_path.join(DEFINITIONS_FILE))?;
+        definitions_file.write_all(&blob)?;
+
+        Ok(())
+    }
+}
+
+
+/// Reads the map and definitions written by FstWriter.
+///
+/// Any bytes can back the map and definitions, so they can be memory mapped rather than read.
+/// Queries return words in sorted order, whose definitions can then be looked up.
+pub struct FstDictionary<D: AsRef<[u8]>>{
+    map: Map<D>,
+    definitions: D
+}
+
+impl FstDictionary<Vec<u8>>{
+    /// Read dictionary.fst and dictionary_definitions.bin from a directory
+    pub fn open(dir_path: &Path) -> Result<FstDictionary<Vec<u8>>>{
+        FstDictionary::from_bytes(fs::read(dir_path.join(MAP_FILE))?, fs::read(dir_path.join(DEFINITIONS_FILE))?)
+    }
+}
+
+impl<D: AsRef<[u8]>> FstDictionary<D>{
+    pub fn from_bytes(map: D, definitions: D) -> Result<FstDictionary<D>>{
+        Ok(FstDictionary { map: Map::new(map)?, definitions })
+    }
+
+    /// Number of words in the dictionary
+    pub fn len(&self) -> usize{
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.map.is_empty()
+    }
+
+    pub fn contains(&self, word: &str) -> bool{
+        self.map.contains_key(word)
+    }
+
+    /// Part of speech, definition and ILI identifier of each sense of a word, in the form used by the index files (e.g. ice_cream)
+    pub fn get(&self, word: &str) -> Option<Vec<(PartOfSpeech, &str, Option<&str>)>>{
+        let id: u64 = self.map.get(word)?;
+
+        self.entry(id as usize)
+    }
+
+    /// Words starting with a prefix
+    pub fn prefix(&self, prefix: &str) -> Result<Vec<String>>{
+        self.search(Str::new(prefix).starts_with())
+    }
+
+    /// Words from start up to but not including end
+    pub fn range(&self, start: &str, end: &str) -> Result<Vec<String>>{
+        Ok(self.map.range().ge(start).lt(end).into_stream().into_str_keys()?)
+    }
+
+    /// Words matched in full by a regex
+    pub fn regex(&self, pattern: &str) -> Result<Vec<String>>{
+        let dfa: DenseDFA<Vec<usize>, usize> = dense::Builder::new().anchored(true).build(pattern)?;
+
+        self.search(&dfa)
+    }
+
+    /// Words within an edit distance of a word
+    pub fn levenshtein(&self, word: &str, distance: u32) -> Result<Vec<String>>{
+        let automaton: Levenshtein = Levenshtein::new(word, distance)?;
+
+        self.search(automaton)
+    }
+
+    fn search<A: Automaton>(&self, automaton: A) -> Result<Vec<String>>{
+        Ok(self.map.search(automaton).into_stream().into_str_keys()?)
+    }
+
+    /// Senses of an entry from the definitions blob
+    fn entry(&self, id: usize) -> Option<Vec<(PartOfSpeech, &str, Option<&str>)>>{
+        let definitions: &[u8] = self.definitions.as_ref();
+        let read_u32 = |index: usize| -> Option<usize>{
+            let bytes: [u8; 4] = definitions.get(index * 4..index * 4 + 4)?.try_into().ok()?;
+            Some(u32::from_le_bytes(bytes) as usize)
+        };
+
+        // Entries start after the count and every offset
+        let count: usize = read_u32(0)?;
+        let entries_start: usize = 4 * (count + 2);
+        let start: usize = entries_start + read_u32(id + 1)?;
+        let end: usize = entries_start + read_u32(id + 2)?;
+        let text: &str = std::str::from_utf8(definitions.get(start..end)?).ok()?;
+
+        text.lines()
+            .map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let (code, ili, definition) = (fields.next()?, fields.next()?, fields.next()?);
+                Some((PartOfSpeech::from_code(code)?, definition, Some(ili).filter(|ili| !ili.is_empty())))
+            })
+            .collect()
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::dictionary_handler::{self, Definitions, Sense, WordData, Words};
+    use crate::output_handler;
+    use regex::Regex;
+
+    const DATA_LINES: [(PartOfSpeech, &str); 4] = [
+        (PartOfSpeech::Noun, "00000100 05 n 01 dog 0 000 | a domestic dog"),
+        (PartOfSpeech::Noun, "00000200 05 n 01 dogwood 0 000 | a tree with showy flowers"),
+        (PartOfSpeech::Noun, "00000300 05 n 01 cat 0 000 | a feline"),
+        (PartOfSpeech::Verb, "00000400 38 v 01 dog 0 000 00 | go after with the intent to catch")
+    ];
+
+    fn sense(part_of_speech: PartOfSpeech, offset: u64, word: &str) -> Sense{
+        Sense {
+            synset: (part_of_speech, offset),
+            sense_number: 1,
+            sense_key: None,
+            frequency: 0,
+            form: word.to_string(),
+            marker: None,
+            frames: vec![],
+            sentences: vec![]
+        }
+    }
+
+    /// Write dog, dogwood and cat with FstWriter to a directory of its own for each test, and read them back
+    fn dictionary(test_name: &str) -> FstDictionary<Vec<u8>>{
+        let definition_regex: Regex = Regex::new(dictionary_handler::DEFINITION_PATTERN).unwrap();
+        let mut definitions: Definitions = Definitions::new();
+
+        for (part_of_speech, line) in DATA_LINES{
+            let definition: Definition = dictionary_handler::parse_data_line(line, part_of_speech, &definition_regex).unwrap();
+            definitions.insert((part_of_speech, definition.offset), definition);
+        }
+
+        definitions.get_mut(&(PartOfSpeech::Noun, 100)).unwrap().ili = Some(String::from("i46360"));
+
+        let mut words: Words = Words::new();
+        words.insert(String::from("dog"), Word { senses: vec![sense(PartOfSpeech::Noun, 100, "dog"), sense(PartOfSpeech::Verb, 400, "dog")], ..Default::default() });
+        words.insert(String::from("dogwood"), Word { senses: vec![sense(PartOfSpeech::Noun, 200, "dogwood")], ..Default::default() });
+        words.insert(String::from("cat"), Word { senses: vec![sense(PartOfSpeech::Noun, 300, "cat")], ..Default::default() });
+
+        let output_path: PathBuf = std::env::temp_dir().join(format!("wordnet2db_{}_{}", test_name, std::process::id()));
+        fs::create_dir_all(&output_path).unwrap();
+
+        let word_data: WordData = (definitions, words);
+        output_handler::write_output(&mut FstWriter::new(&output_path), &word_data).unwrap();
+
+        let dictionary: FstDictionary<Vec<u8>> = FstDictionary::open(&output_path).unwrap();
+        fs::remove_dir_all(&output_path).unwrap();
+
+        dictionary
+    }
+
+    #[test]
+    fn round_trip(){
+        let dictionary: FstDictionary<Vec<u8>> = dictionary("round_trip");
+
+        assert_eq!(dictionary.len(), 3);
+        assert!(dictionary.contains("dogwood"));
+        assert!(!dictionary.contains("do"));
+        assert_eq!(dictionary.get("dog"), Some(vec![(PartOfSpeech::Noun, "a domestic dog", Some("i46360")), (PartOfSpeech::Verb, "go after with the intent to catch", None)]));
+        assert_eq!(dictionary.get("cat"), Some(vec![(PartOfSpeech::Noun, "a feline", None)]));
+        assert_eq!(dictionary.get("cow"), None);
+    }
+
+    #[test]
+    fn queries(){
+        let dictionary: FstDictionary<Vec<u8>> = dictionary("queries");
+
+        assert_eq!(dictionary.prefix("dog").unwrap(), ["dog", "dogwood"]);
+        assert_eq!(dictionary.range("c", "dogw").unwrap(), ["cat", "dog"]);
+        assert_eq!(dictionary.regex("d.*d").unwrap(), ["dogwood"]);
+        assert_eq!(dictionary.regex("og").unwrap(), Vec::<String>::new());
+        assert_eq!(dictionary.levenshtein("cot", 1).unwrap(), ["cat"]);
+        assert_eq!(dictionary.levenshtein("dig", 1).unwrap(), ["dog"]);
+    }
+}
@@ -0,0 +1,307 @@
+use anyhow::{bail, Result};
+use crate::dictionary_handler::{self, Definition, Word};
+use crate::output_handler::{OutputWriter, Relation, SenseRelation, WordSense};
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+
+/// Escape a value for use in XML text or attributes
+fn escape_xml(value: &str) -> String{
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+
+/// Escape a value for use in a double quoted DOT string
+fn escape_dot(value: &str) -> String{
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+
+/// Writes synsets as nodes and pointers as typed edges in GraphML, for tools like Gephi
+pub struct GraphmlWriter{
+    output_path: PathBuf,
+    node_ids: HashMap<i64, String>,
+    graphml: String
+}
+
+impl GraphmlWriter{
+    pub fn new(output_path: &Path) -> GraphmlWriter{
+        GraphmlWriter { output_path: output_path.to_owned(), node_ids: HashMap::new(), graphml: String::new() }
+    }
+}
+
+impl OutputWriter for GraphmlWriter{
+    fn label(&self) -> &str{
+        "GraphML"
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating GraphML...");
+
+        // Write the header and attribute keys
+        self.graphml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        self.graphml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        self.graphml.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+        self.graphml.push_str("  <key id=\"definition\" for=\"node\" attr.name=\"definition\" attr.type=\"string\"/>\n");
+        self.graphml.push_str("  <key id=\"part_of_speech\" for=\"node\" attr.name=\"part_of_speech\" attr.type=\"string\"/>\n");
+        self.graphml.push_str("  <key id=\"lexname\" for=\"node\" attr.name=\"lexname\" attr.type=\"string\"/>\n");
+        self.graphml.push_str("  <key id=\"ili\" for=\"node\" attr.name=\"ili\" attr.type=\"string\"/>\n");
+        self.graphml.push_str("  <key id=\"type\" for=\"edge\" attr.name=\"type\" attr.type=\"string\"/>\n");
+        self.graphml.push_str("  <graph id=\"wordnet\" edgedefault=\"directed\">\n");
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, id: i64, definition: &Definition) -> Result<()>{
+        let node_id: String = definition.synset_id();
+
+        // Write the node with its attributes
+        self.graphml.push_str(&format!("    <node id=\"{}\">\n", node_id));
+        self.graphml.push_str(&format!("      <data key=\"label\">{}</data>\n", escape_xml(&definition.display_words().join(", "))));
+        self.graphml.push_str(&format!("      <data key=\"definition\">{}</data>\n", escape_xml(&definition.data)));
+        self.graphml.push_str(&format!("      <data key=\"part_of_speech\">{}</data>\n", escape_xml(definition.part_of_speech.name())));
+
+        if let Some(lexname) = &definition.lexname{
+            self.graphml.push_str(&format!("      <data key=\"lexname\">{}</data>\n", escape_xml(lexname)));
+        }
+
+        if let Some(ili) = &definition.ili{
+            self.graphml.push_str(&format!("      <data key=\"ili\">{}</data>\n", escape_xml(ili)));
+        }
+
+        self.graphml.push_str("    </node>\n");
+
+        self.node_ids.insert(id, node_id);
+
+        Ok(())
+    }
+
+    fn write_word(&mut self, _id: i64, _word: &str, _entry: &Word, _senses: &[WordSense]) -> Result<()>{
+        Ok(())
+    }
+
+    fn write_relation(&mut self, relation: &Relation) -> Result<()>{
+        self.graphml.push_str(&format!("    <edge source=\"{}\" target=\"{}\">\n", self.node_ids[&relation.source_id], self.node_ids[&relation.target_id]));
+        self.graphml.push_str(&format!("      <data key=\"type\">{}</data>\n", relation.pointer.relation_name()));
+        self.graphml.push_str("    </edge>\n");
+
+        Ok(())
+    }
+
+    fn write_sense_relation(&mut self, relation: &SenseRelation) -> Result<()>{
+        // Nodes are synsets, so join the synsets of the two words
+        self.write_relation(&Relation { source_id: relation.source_synset_id, target_id: relation.target_synset_id, pointer: relation.pointer })
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        self.graphml.push_str("  </graph>\n");
+        self.graphml.push_str("</graphml>\n");
+
+        // Save to file
+        let mut file = File::create(self.output_path.join("dictionary.graphml"))?;
+        file.write_all(self.graphml.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+
+/// Writes synsets as nodes and pointers as labelled edges in Graphviz DOT.
+///
+/// When a root word is given, only the synsets of that word and the hyponyms below them
+/// (down to an optional depth) are written.
+pub struct DotWriter{
+    output_path: PathBuf,
+    root: Option<String>,
+    depth: Option<u32>,
+    nodes: Vec<(i64, String, String, Option<String>)>,
+    edges: Vec<(i64, i64, String)>,
+    root_ids: Vec<i64>
+}
+
+impl DotWriter{
+    pub fn new(output_path: &Path, root: Option<&str>, depth: Option<u32>) -> DotWriter{
+        // Match the root in the form used by the index files
+        let root: Option<String> = root.map(dictionary_handler::normalize_word);
+
+        DotWriter { output_path: output_path.to_owned(), root, depth, nodes: vec![], edges: vec![], root_ids: vec![] }
+    }
+
+    /// Synsets within the depth limit below the root word's synsets
+    fn subtree(&self) -> HashSet<i64>{
+        // Map every synset to the synsets directly below it
+        let mut children: HashMap<i64, Vec<i64>> = HashMap::new();
+
+        for (source, target, relation) in &self.edges{
+            match relation.as_str(){
+                "hypernym" | "instance_hypernym" => children.entry(*target).or_default().push(*source),
+                "hyponym" | "instance_hyponym" => children.entry(*source).or_default().push(*target),
+                _ => {}
+            }
+        }
+
+        // Walk down from the root breadth first
+        let mut included: HashSet<i64> = self.root_ids.iter().copied().collect();
+        let mut frontier: Vec<i64> = self.root_ids.clone();
+        let mut level: u32 = 0;
+
+        while !frontier.is_empty() && self.depth.is_none_or(|depth| level < depth){
+            let mut next: Vec<i64> = vec![];
+
+            for id in frontier{
+                for child in children.get(&id).into_iter().flatten(){
+                    if included.insert(*child){
+                        next.push(*child);
+                    }
+                }
+            }
+
+            frontier = next;
+            level += 1;
+        }
+
+        included
+    }
+}
+
+impl OutputWriter for DotWriter{
+    fn label(&self) -> &str{
+        "DOT"
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating DOT...");
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, id: i64, definition: &Definition) -> Result<()>{
+        self.nodes.push((id, definition.synset_id(), definition.display_words().join(", "), definition.ili.clone()));
+
+        Ok(())
+    }
+
+    fn write_word(&mut self, _id: i64, word: &str, _entry: &Word, senses: &[WordSense]) -> Result<()>{
+        // Remember the synsets of the root word
+        if self.root.as_deref() == Some(word){
+            self.root_ids.extend(senses.iter().map(|sense| sense.synset_id));
+        }
+
+        Ok(())
+    }
+
+    fn write_relation(&mut self, relation: &Relation) -> Result<()>{
+        self.edges.push((relation.source_id, relation.target_id, relation.pointer.relation_name().to_string()));
+
+        Ok(())
+    }
+
+    fn write_sense_relation(&mut self, relation: &SenseRelation) -> Result<()>{
+        // Draw lexical relations between the synsets of the words
+        self.write_relation(&Relation { source_id: relation.source_synset_id, target_id: relation.target_synset_id, pointer: relation.pointer })
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        // Get the synsets to write
+        let included: Option<HashSet<i64>> = match &self.root{
+            Some(root) if self.root_ids.is_empty() => bail!(format!("Root word {} was not found!", root).red()),
+            Some(_) => Some(self.subtree()),
+            None => None
+        };
+
+        let is_included = |id: &i64| included.as_ref().is_none_or(|included| included.contains(id));
+
+        // Write nodes and edges between them
+        let mut dot: String = String::from("digraph wordnet {\n");
+        let mut node_ids: HashMap<i64, &str> = HashMap::new();
+
+        for (id, node_id, label, ili) in &self.nodes{
+            if is_included(id){
+                let ili_attribute: String = ili.as_ref().map_or(String::new(), |ili| format!(", ili=\"{}\"", escape_dot(ili)));
+                dot.push_str(&format!("  \"{}\" [label=\"{}\"{}];\n", node_id, escape_dot(label), ili_attribute));
+                node_ids.insert(*id, node_id);
+            }
+        }
+
+        for (source, target, relation) in &self.edges{
+            if let (Some(source), Some(target)) = (node_ids.get(source), node_ids.get(target)){
+                dot.push_str(&format!("  \"{}\" -> \"{}\" [label=\"{}\"];\n", source, target, relation));
+            }
+        }
+
+        dot.push_str("}\n");
+
+        // Save to file
+        let mut file = File::create(self.output_path.join("dictionary.dot"))?;
+        file.write_all(dot.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+
+/// Writes pointers as a tab separated source, target and relation edge list
+pub struct EdgeListWriter{
+    output_path: PathBuf,
+    node_ids: HashMap<i64, String>,
+    tsv: String
+}
+
+impl EdgeListWriter{
+    pub fn new(output_path: &Path) -> EdgeListWriter{
+        EdgeListWriter { output_path: output_path.to_owned(), node_ids: HashMap::new(), tsv: String::new() }
+    }
+}
+
+impl OutputWriter for EdgeListWriter{
+    fn label(&self) -> &str{
+        "Edge list"
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating edge list...");
+
+        self.tsv.push_str("source\ttarget\ttype\n");
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, id: i64, definition: &Definition) -> Result<()>{
+        self.node_ids.insert(id, definition.synset_id());
+
+        Ok(())
+    }
+
+    fn write_word(&mut self, _id: i64, _word: &str, _entry: &Word, _senses: &[WordSense]) -> Result<()>{
+        Ok(())
+    }
+
+    fn write_relation(&mut self, relation: &Relation) -> Result<()>{
+        self.tsv.push_str(&format!("{}\t{}\t{}\n", self.node_ids[&relation.source_id], self.node_ids[&relation.target_id], relation.pointer.relation_name()));
+
+        Ok(())
+    }
+
+    fn write_sense_relation(&mut self, relation: &SenseRelation) -> Result<()>{
+        // Edges are only kept between synsets
+        self.write_relation(&Relation { source_id: relation.source_synset_id, target_id: relation.target_synset_id, pointer: relation.pointer })
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        // Save to file
+        let mut file = File::create(self.output_path.join("dictionary_edges.tsv"))?;
+        file.write_all(self.tsv.as_bytes())?;
+
+        Ok(())
+    }
+}
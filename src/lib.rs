@@ -0,0 +1,327 @@
+pub mod codegen_handler;
+pub mod db_handler;
+pub mod dictionary_handler;
+pub mod file_handler;
+pub mod fst_handler;
+pub mod graph_handler;
+pub mod output_handler;
+pub mod phonetic_handler;
+pub mod pronunciation_handler;
+pub mod rdf_handler;
+pub mod similarity_handler;
+pub mod suggest_handler;
+
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use colored::Colorize;
+use dictionary_handler::WordData;
+use file_handler::IndexDataPair;
+use output_handler::{OutputWriter, WriterRegistry};
+use regex::Regex;
+use std::env::current_dir;
+use std::path::PathBuf;
+
+// Parser setup
+#[derive(Parser)]
+#[command(name = "WordNet Parser")]
+#[command(version = "1.0.0")]
+/// Parse Princeton University's WordNet files.
+///
+/// Get a list of English words & definitions by parsing Princeton's WordNet files
+///
+/// Outputs an SQLite database, SQL statements, or a JSON file
+pub struct Args {
+    /// Only keep words made entirely of these characters, with a hyphen between two characters for a range e.g. a-z
+    #[arg(long)]
+    pub alphabet: Option<String>,
+    /// Only keep words that are an anagram of these letters
+    #[arg(long, value_name = "LETTERS")]
+    pub anagram_of: Option<String>,
+    /// Namespace that word, sense and synset IRIs are minted under in RDF output
+    #[arg(long, value_name = "URI", default_value = "http://localhost/wordnet/")]
+    pub base_uri: String,
+    /// Comma seperated list of character counts to save (counted in grapheme clusters)
+    #[arg(short, long, value_delimiter = ',', num_args = 0.., conflicts_with_all = ["min_chars", "max_chars"])]
+    pub char_counts: Vec<usize>,
+    /// Also write an import.cypher script that loads the Neo4j CSV files with LOAD CSV
+    #[arg(long, default_value_t = false)]
+    pub cypher: bool,
+    /// Local copy of the CMU Pronouncing Dictionary (cmudict-0.7b) to add pronunciations, syllable counts and rhymes from
+    #[arg(long, value_name = "FILE")]
+    pub cmudict: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Directory where WordNet files are located (index.adj, index.adv, data.adj, etc)
+    #[arg(short, long)]
+    pub directory: PathBuf,
+    /// Levels of hyponyms below the root word to include in DOT output
+    #[arg(long, requires = "root")]
+    pub depth: Option<u32>,
+    /// Renders database as SQL statements rather than an SQLite database
+    #[arg(short = 'S', long, default_value_t = false)]
+    pub dump_sql: bool,
+    /// Drop definitions matching this regex (repeatable)
+    #[arg(long, value_name = "REGEX")]
+    pub exclude_definition: Vec<Regex>,
+    /// Drop words matching this regex (repeatable)
+    #[arg(long, value_name = "REGEX")]
+    pub exclude_word: Vec<Regex>,
+    /// Only keep words without any of these letters
+    #[arg(long, value_name = "LETTERS")]
+    pub forbid_letters: Option<String>,
+    /// Output format to render (sqlite, sql, json, graphml, dot, tsv, neo4j, turtle, ntriples, rust, fst, or any registered writer)
+    #[arg(short, long, conflicts_with_all = ["dump_sql", "to_json"])]
+    pub format: Option<String>,
+    /// Interlingual Index mapping file (ili-map-pwn30.tab or ili-map-pwn31.tab) to add the ILI identifier of each synset from
+    #[arg(long, value_name = "FILE")]
+    pub ili: Option<PathBuf>,
+    /// Only keep words matching at least one of these regexes (repeatable)
+    #[arg(long, value_name = "REGEX")]
+    pub include_word: Vec<Regex>,
+    /// Keep words with numbers
+    #[arg(short, long, default_value_t = false)]
+    pub keep_numbers: bool,
+    /// Comma seperated list of characters to leave out when counting word length e.g. spaces,hyphens
+    #[arg(long, value_delimiter = ',', value_enum)]
+    pub length_excludes: Vec<LengthExclude>,
+    /// Comma seperated list of lexicographer file names to save definitions from e.g. noun.food,noun.plant
+    #[arg(long, value_delimiter = ',', value_name = "LEXNAMES")]
+    pub lexname: Vec<String>,
+    /// Maximum character count of a word to save (counted in grapheme clusters)
+    #[arg(short = 'M', long, default_value_t = 45)]
+    pub max_chars: usize,
+    /// Drop senses tagged fewer than this many times in the semantic concordances
+    #[arg(long, value_name = "COUNT")]
+    pub min_frequency: Option<u32>,
+    /// Minimum character count of a word to save (counted in grapheme clusters)
+    #[arg(short = 'm', long, default_value_t = 0)]
+    pub min_chars: usize,
+    /// Open Multilingual Wordnet tab file (wn-data-xxx.tab) to add translations of each synset from (repeatable)
+    #[arg(long, value_name = "FILE")]
+    pub omw: Vec<PathBuf>,
+    /// Only keep words without punctuation or spaces
+    #[arg(short = 'W', long, default_value_t = false)]
+    pub only_whole_words: bool,
+    /// Directory to place output file into (default: working directory)
+    #[arg(short, long)]
+    pub output_directory: Option<PathBuf>,
+    /// Only keep words matching a crossword style pattern where ? is any character e.g. ?a??e
+    #[arg(long)]
+    pub pattern: Option<String>,
+    /// Add Soundex and Double Metaphone keys of each word to the word table and JSON output, for sound-alike matching
+    #[arg(long, default_value_t = false)]
+    pub phonetic: bool,
+    /// Comma seperated list of parts of speech to save e.g. noun,verb
+    #[arg(long, value_delimiter = ',')]
+    pub pos: Vec<PartOfSpeech>,
+    /// Write parts of speech as WordNet's single letter codes (n, v, a, s, r) instead of full names in SQL and JSON output
+    #[arg(long, default_value_t = false)]
+    pub pos_codes: bool,
+    /// Display words with the capitalization they have in the data files (e.g. Paris rather than paris)
+    #[arg(long, default_value_t = false)]
+    pub preserve_case: bool,
+    /// Only keep words containing all of these letters, as many times as they're repeated e.g. ee
+    #[arg(long, value_name = "LETTERS")]
+    pub require_letters: Option<String>,
+    /// Only include this word's synsets and the hyponyms below them in DOT output
+    #[arg(long, value_name = "WORD")]
+    pub root: Option<String>,
+    /// Collection to embed words in for Rust output
+    #[arg(long, value_enum, default_value_t = RustCollection::Slice)]
+    pub rust_collection: RustCollection,
+    /// Shape of the tables in SQLite and SQL output
+    #[arg(long, value_enum, default_value_t = Schema::Normalized)]
+    pub schema: Schema,
+    /// File of words to drop, one per line
+    #[arg(long, value_name = "FILE")]
+    pub stoplist: Option<PathBuf>,
+    /// Only keep this many of the most frequent words
+    #[arg(long, value_name = "N")]
+    pub top_n: Option<usize>,
+    /// Renders dictionary as JSON rather than an SQLite database
+    #[arg(short = 'J',long, default_value_t = false, conflicts_with = "dump_sql")]
+    pub to_json: bool,
+    /// Add a synset_ancestor table with every hypernym of every synset, and a depth column on each synset
+    #[arg(long, default_value_t = false)]
+    pub with_closure: bool,
+    /// File of words to keep, one per line. Other words are dropped
+    #[arg(long, value_name = "FILE")]
+    pub wordlist: Option<PathBuf>
+}
+
+#[derive(Subcommand)]
+pub enum Command{
+    /// Print how similar two words are by each similarity measure
+    Similarity{
+        word1: String,
+        word2: String
+    },
+    /// Print the words closest to a possibly misspelled word, by edit distance
+    Suggest{
+        word: String,
+        /// Most edits a suggestion can be away from the word
+        #[arg(long, default_value_t = 2)]
+        max_distance: usize,
+        /// Most suggestions to print
+        #[arg(long, default_value_t = 10)]
+        limit: usize
+    }
+}
+
+/// Characters that can be left out of word lengths
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum LengthExclude{
+    /// Spaces, including the underscores WordNet uses in collocations
+    Spaces,
+    Hyphens,
+    Apostrophes
+}
+
+/// Table layouts for SQLite and SQL output
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Schema{
+    /// Synsets, words, senses and the relations between them
+    Normalized,
+    /// A single entry(word, pos, sense_number, definition) table
+    Flat,
+    /// A word_json(word, data) table holding each word as the JSON object JSON output uses
+    Kv
+}
+
+/// Collections Rust output can embed words in
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum RustCollection{
+    /// A sorted static slice, searched with binary_search_by_key
+    Slice,
+    /// A phf::Map built with the phf_map! macro
+    Phf
+}
+
+/// Part of speech of a synset. Data files are named after four of them, and satellites are found in the adjective files
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
+pub enum PartOfSpeech{
+    Noun,
+    Verb,
+    Adjective,
+    #[value(skip)]
+    AdjectiveSatellite,
+    Adverb
+}
+
+impl PartOfSpeech{
+    pub const ALL: [PartOfSpeech; 5] = [PartOfSpeech::Noun, PartOfSpeech::Verb, PartOfSpeech::Adjective, PartOfSpeech::AdjectiveSatellite, PartOfSpeech::Adverb];
+
+    /// Part of speech of the index and data files with this extension (e.g. adj)
+    pub fn from_extension(extension: &str) -> Option<PartOfSpeech>{
+        match extension{
+            "noun" => Some(PartOfSpeech::Noun),
+            "verb" => Some(PartOfSpeech::Verb),
+            "adj" => Some(PartOfSpeech::Adjective),
+            "adv" => Some(PartOfSpeech::Adverb),
+            _ => None
+        }
+    }
+
+    /// Part of speech of a synset type or pointer symbol (n, v, a, s or r)
+    pub fn from_code(code: &str) -> Option<PartOfSpeech>{
+        PartOfSpeech::ALL.into_iter().find(|part_of_speech| part_of_speech.code() == code)
+    }
+
+    /// WordNet's single letter code
+    pub fn code(&self) -> &'static str{
+        match self{
+            PartOfSpeech::Noun => "n",
+            PartOfSpeech::Verb => "v",
+            PartOfSpeech::Adjective => "a",
+            PartOfSpeech::AdjectiveSatellite => "s",
+            PartOfSpeech::Adverb => "r"
+        }
+    }
+
+    pub fn name(&self) -> &'static str{
+        match self{
+            PartOfSpeech::Noun => "noun",
+            PartOfSpeech::Verb => "verb",
+            PartOfSpeech::Adjective => "adjective",
+            PartOfSpeech::AdjectiveSatellite => "adjective satellite",
+            PartOfSpeech::Adverb => "adverb"
+        }
+    }
+
+    /// Code or full name, as chosen with --pos-codes
+    pub fn label(&self, codes: bool) -> &'static str{
+        if codes{
+            self.code()
+        } else{
+            self.name()
+        }
+    }
+
+    /// ID of the part of speech in the part_of_speech table
+    pub fn id(&self) -> i64{
+        PartOfSpeech::ALL.iter().position(|part_of_speech| part_of_speech == self).unwrap_or(0) as i64 + 1
+    }
+
+    /// Part of speech of the data file the synset is in, which is adjective for satellites
+    pub fn data_file(&self) -> PartOfSpeech{
+        match self{
+            PartOfSpeech::AdjectiveSatellite => PartOfSpeech::Adjective,
+            _ => *self
+        }
+    }
+}
+
+impl Args{
+    /// Name of the writer to render output with. The -S and -J flags are shorthands for the sql and json writers
+    pub fn format_name(&self) -> &str{
+        if self.dump_sql{
+            "sql"
+        } else if self.to_json{
+            "json"
+        } else{
+            self.format.as_deref().unwrap_or("sqlite")
+        }
+    }
+}
+
+
+/// Run the command line with parsed arguments, rendering output with a writer from the registry.
+///
+/// Crates with their own writers can register them on `WriterRegistry::with_builtin_writers()` and pass it here to select them with --format.
+pub fn run(args: &Args, registry: &WriterRegistry) -> Result<()>{
+    // Verify input directory is valid before proceeding
+    if file_handler::is_valid_dir(&args.directory)?{
+        // Get file paths
+        let path_pairs: Vec<IndexDataPair> = file_handler::get_paths(&args.directory)?;
+
+        // Run any subcommand instead of creating output
+        match &args.command{
+            Some(Command::Similarity { word1, word2 }) => return similarity_handler::print_word_similarity(&path_pairs, &args.directory, word1, word2),
+            Some(Command::Suggest { word, max_distance, limit }) => return suggest_handler::print_suggestions(&path_pairs, args, word, *max_distance, *limit),
+            None => {}
+        }
+
+        // Get word data
+        let word_data: WordData = dictionary_handler::get_word_data(&path_pairs, args)?;
+
+        // Throw an error if no words found
+        if word_data.1.is_empty(){
+            bail!("No words found for given arguments!".red())
+        }
+
+        // Check if an output path is specified and is valid
+        let output_path: PathBuf = match &args.output_directory{
+            Some(output) if file_handler::is_valid_dir(output)? => output.to_owned(),
+            _ => current_dir()?
+        };
+
+        // Create the writer for the requested format and render the output
+        let mut writer: Box<dyn OutputWriter> = registry.create(args.format_name(), &output_path, args)?;
+        output_handler::write_output(writer.as_mut(), &word_data)?;
+
+        // Print status message
+        println!("{}", format!("{} created successfully!", writer.label()).green());
+    }
+
+    Ok(())
+}
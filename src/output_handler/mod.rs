@@ -0,0 +1,257 @@
+use anyhow::{bail, Result};
+use crate::Args;
+use crate::codegen_handler::RustWriter;
+use crate::db_handler::{Neo4jWriter, SqlWriter, SqliteWriter};
+use crate::dictionary_handler::{self, Definition, JsonWriter, Pointer, Sense, SynsetKey, Word, WordData};
+use crate::fst_handler::FstWriter;
+use crate::graph_handler::{DotWriter, EdgeListWriter, GraphmlWriter};
+use crate::rdf_handler::{RdfWriter, Syntax};
+use colored::Colorize;
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::path::Path;
+
+pub type WriterFactory = Box<dyn Fn(&Path, &Args) -> Result<Box<dyn OutputWriter>>>;
+
+/// A sense of a word, along with its ID and the ID its synset was written with
+pub struct WordSense<'a>{
+    pub id: i64,
+    pub synset_id: i64,
+    pub definition: &'a Definition,
+    pub sense: &'a Sense
+}
+
+/// A pointer between two synsets that were both written
+pub struct Relation<'a>{
+    pub source_id: i64,
+    pub target_id: i64,
+    pub pointer: &'a Pointer
+}
+
+/// A pointer between specific words of two synsets (e.g. hot and cold), whose senses were both written
+pub struct SenseRelation<'a>{
+    pub source_id: i64,
+    pub target_id: i64,
+    pub source_synset_id: i64,
+    pub target_synset_id: i64,
+    pub pointer: &'a Pointer
+}
+
+/// Renders word data into an output format.
+///
+/// Writers are driven by `write_output`, which assigns IDs and calls the hooks in order:
+/// `begin`, then `write_synset`/`write_word` as words are visited, then the head synsets of satellites and the ancestors of synsets
+/// that no word references, then `write_relation`/`write_sense_relation`, then `write_adjective_cluster`, then `write_ancestor`, then `finish`.
+/// A synset is always written before the first word that references it.
+pub trait OutputWriter{
+    /// Name of the output shown in status messages (e.g. "Database")
+    fn label(&self) -> &str;
+
+    /// Called once before anything is written
+    fn begin(&mut self) -> Result<()>{
+        Ok(())
+    }
+
+    /// Called once for every synset referenced by a saved word, or needed to complete a satellite cluster or closure
+    fn write_synset(&mut self, id: i64, definition: &Definition) -> Result<()>;
+
+    /// Called once for every saved word with the synsets it belongs to
+    fn write_word(&mut self, id: i64, word: &str, entry: &Word, senses: &[WordSense]) -> Result<()>;
+
+    /// Called once for every pointer between whole synsets whose source and target were both written
+    fn write_relation(&mut self, _relation: &Relation) -> Result<()>{
+        Ok(())
+    }
+
+    /// Called once for every pointer between specific words whose source and target senses were both written
+    fn write_sense_relation(&mut self, _relation: &SenseRelation) -> Result<()>{
+        Ok(())
+    }
+
+    /// Called once for every written satellite adjective with the head synset it's clustered around
+    fn write_adjective_cluster(&mut self, _head_id: i64, _satellite_id: i64) -> Result<()>{
+        Ok(())
+    }
+
+    /// Called once for every ancestor of a written synset that was also written, when the closure was computed
+    fn write_ancestor(&mut self, _synset_id: i64, _ancestor_id: i64, _depth: u32) -> Result<()>{
+        Ok(())
+    }
+
+    /// Called once after everything has been written
+    fn finish(&mut self) -> Result<()>;
+}
+
+
+/// Output writers available by format name
+#[derive(Default)]
+pub struct WriterRegistry{
+    factories: BTreeMap<String, WriterFactory>
+}
+
+impl WriterRegistry{
+    pub fn new() -> WriterRegistry{
+        WriterRegistry::default()
+    }
+
+    /// Registry containing the sqlite, sql, json, graphml, dot, tsv, neo4j, turtle, ntriples, rust and fst writers
+    pub fn with_builtin_writers() -> WriterRegistry{
+        let mut registry: WriterRegistry = WriterRegistry::new();
+
+        registry.register("sqlite", |output_path, args| Ok(Box::new(SqliteWriter::new(output_path, args.schema, args.with_closure, args.phonetic, args.pos_codes)?)));
+        registry.register("sql", |output_path, args| Ok(Box::new(SqlWriter::new(output_path, args.schema, args.with_closure, args.phonetic, args.pos_codes))));
+        registry.register("json", |output_path, args| Ok(Box::new(JsonWriter::new(output_path, args.pos_codes))));
+        registry.register("graphml", |output_path, _| Ok(Box::new(GraphmlWriter::new(output_path))));
+        registry.register("dot", |output_path, args| Ok(Box::new(DotWriter::new(output_path, args.root.as_deref(), args.depth))));
+        registry.register("tsv", |output_path, _| Ok(Box::new(EdgeListWriter::new(output_path))));
+        registry.register("neo4j", |output_path, args| Ok(Box::new(Neo4jWriter::new(output_path, args.cypher))));
+        registry.register("turtle", |output_path, args| Ok(Box::new(RdfWriter::new(output_path, Syntax::Turtle, &args.base_uri))));
+        registry.register("ntriples", |output_path, args| Ok(Box::new(RdfWriter::new(output_path, Syntax::NTriples, &args.base_uri))));
+        registry.register("rust", |output_path, args| Ok(Box::new(RustWriter::new(output_path, args.rust_collection))));
+        registry.register("fst", |output_path, _| Ok(Box::new(FstWriter::new(output_path))));
+
+        registry
+    }
+
+    /// Add a writer under the given format name, replacing any writer already using it
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(&Path, &Args) -> Result<Box<dyn OutputWriter>> + 'static
+    {
+        self.factories.insert(name.to_string(), Box::new(factory));
+    }
+
+    /// Create the writer registered under the given format name
+    pub fn create(&self, name: &str, output_path: &Path, args: &Args) -> Result<Box<dyn OutputWriter>>{
+        match self.factories.get(name){
+            Some(factory) => factory(output_path, args),
+            None => bail!(format!("Unknown format {}! Available formats: {}", name, self.names().collect::<Vec<&str>>().join(", ")).red())
+        }
+    }
+
+    /// Every registered format name in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str>{
+        self.factories.keys().map(|name| name.as_str())
+    }
+}
+
+
+pub fn write_output(writer: &mut dyn OutputWriter, word_data: &WordData) -> Result<()>{
+    // Get words and definitions
+    let (definitions, words) = word_data;
+
+    // Create a hashmap to store synset and id values
+    let mut synset_ids: HashMap<&SynsetKey, i64> = HashMap::new();
+
+    // Keep the order synsets were written in for relations
+    let mut written_synsets: Vec<(&SynsetKey, i64)> = vec![];
+
+    // Create a hashmap to store the ids of senses by word and synset
+    let mut sense_ids: HashMap<(&str, &SynsetKey), i64> = HashMap::new();
+
+    writer.begin()?;
+
+    // Process words and definitions
+    for (word_id, (word, entry)) in (1..).zip(words){
+        // Create a vector to store the senses of the word
+        let mut senses: Vec<WordSense> = vec![];
+
+        for sense in &entry.senses{
+            // Skip synsets that weren't loaded
+            let Some(definition) = definitions.get(&sense.synset) else{
+                continue;
+            };
+
+            // Write the synset if this is the first word referencing it
+            let synset_id: i64 = match synset_ids.entry(&sense.synset){
+                Entry::Occupied(e) => *e.get(),
+                Entry::Vacant(e) => {
+                    let synset_id: i64 = written_synsets.len() as i64 + 1;
+                    writer.write_synset(synset_id, definition)?;
+                    written_synsets.push((&sense.synset, synset_id));
+                    *e.insert(synset_id)
+                }
+            };
+
+            let id: i64 = sense_ids.len() as i64 + 1;
+            sense_ids.insert((word, &sense.synset), id);
+
+            senses.push(WordSense { id, synset_id, definition, sense });
+        }
+
+        writer.write_word(word_id, word, entry, &senses)?;
+    }
+
+    // Write the head synsets of satellites so every cluster is complete
+    let heads: Vec<&SynsetKey> = written_synsets.iter().filter_map(|(key, _)| definitions[*key].satellite_head()).collect();
+
+    for head in heads{
+        if let (Some(definition), Entry::Vacant(e)) = (definitions.get(head), synset_ids.entry(head)){
+            let synset_id: i64 = written_synsets.len() as i64 + 1;
+            writer.write_synset(synset_id, definition)?;
+            written_synsets.push((head, synset_id));
+            e.insert(synset_id);
+        }
+    }
+
+    // Write the ancestors of every synset, which aren't always referenced by a kept word, so the closure is complete
+    let ancestors: Vec<&SynsetKey> = written_synsets.iter().flat_map(|(key, _)| definitions[*key].ancestors.iter().map(|(ancestor, _)| ancestor)).collect();
+
+    for ancestor in ancestors{
+        if let (Some(definition), Entry::Vacant(e)) = (definitions.get(ancestor), synset_ids.entry(ancestor)){
+            let synset_id: i64 = written_synsets.len() as i64 + 1;
+            writer.write_synset(synset_id, definition)?;
+            written_synsets.push((ancestor, synset_id));
+            e.insert(synset_id);
+        }
+    }
+
+    // Write pointers between synsets that were both written
+    for (key, source_synset_id) in &written_synsets{
+        for pointer in &definitions[*key].pointers{
+            let Some(target_synset_id) = synset_ids.get(&pointer.target) else{
+                continue;
+            };
+
+            // Pointers between whole synsets have no word numbers
+            if pointer.source_word == 0 && pointer.target_word == 0{
+                writer.write_relation(&Relation { source_id: *source_synset_id, target_id: *target_synset_id, pointer })?;
+                continue;
+            }
+
+            // Find the senses of the words the pointer is between
+            let lemma = |key: &SynsetKey, word_number: u8| definitions[key].words.get((word_number as usize).checked_sub(1)?).map(|word| dictionary_handler::base_lemma(&word.data));
+
+            let (Some(source_lemma), Some(target_lemma)) = (lemma(key, pointer.source_word), lemma(&pointer.target, pointer.target_word)) else{
+                continue;
+            };
+
+            if let (Some(source_id), Some(target_id)) = (sense_ids.get(&(source_lemma.as_str(), *key)), sense_ids.get(&(target_lemma.as_str(), &pointer.target))){
+                writer.write_sense_relation(&SenseRelation {
+                    source_id: *source_id,
+                    target_id: *target_id,
+                    source_synset_id: *source_synset_id,
+                    target_synset_id: *target_synset_id,
+                    pointer
+                })?;
+            }
+        }
+    }
+
+    // Write the head of every satellite
+    for (key, satellite_id) in &written_synsets{
+        if let Some(head_id) = definitions[*key].satellite_head().and_then(|head| synset_ids.get(head)){
+            writer.write_adjective_cluster(*head_id, *satellite_id)?;
+        }
+    }
+
+    // Write the ancestors of every synset
+    for (key, synset_id) in &written_synsets{
+        for (ancestor, depth) in &definitions[*key].ancestors{
+            if let Some(ancestor_id) = synset_ids.get(ancestor){
+                writer.write_ancestor(*synset_id, *ancestor_id, *depth)?;
+            }
+        }
+    }
+
+    writer.finish()
+}
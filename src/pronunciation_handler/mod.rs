@@ -0,0 +1,69 @@
+use serde::Serialize;
+
+
+/// A pronunciation of a word from the CMU Pronouncing Dictionary
+#[derive(Clone, Serialize)]
+pub struct Pronunciation{
+    /// ARPAbet phonemes separated by spaces, with a stress digit on each vowel (e.g. K AE1 T)
+    pub phonemes: String,
+    /// Stress digit of each vowel in order, where 1 is primary, 2 is secondary and 0 is unstressed (e.g. 100 for cinema)
+    pub stress: String,
+    pub syllables: u32,
+    /// Phonemes from the last primary stressed vowel to the end, shared by words that rhyme (e.g. AE1 T for cat and hat)
+    pub rhyme: String
+}
+
+impl Pronunciation{
+    pub fn new(phonemes: &str) -> Pronunciation{
+        let phonemes: Vec<&str> = phonemes.split_whitespace().collect();
+
+        // Vowels are the only phonemes carrying a stress digit
+        let stress: String = phonemes.iter().filter_map(|phoneme| phoneme.chars().last().filter(char::is_ascii_digit)).collect();
+
+        // Rhymes start at the last primary stressed vowel, or the last vowel for words without one
+        let rhyme_start: usize = phonemes.iter().rposition(|phoneme| phoneme.ends_with('1'))
+            .or_else(|| phonemes.iter().rposition(|phoneme| phoneme.ends_with(|last: char| last.is_ascii_digit())))
+            .unwrap_or(0);
+
+        Pronunciation {
+            phonemes: phonemes.join(" "),
+            syllables: stress.len() as u32,
+            stress,
+            rhyme: phonemes[rhyme_start..].join(" ")
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn stress_and_syllables(){
+        let cinema: Pronunciation = Pronunciation::new("S IH1 N AH0 M AH0");
+        let understand: Pronunciation = Pronunciation::new("AH2 N D ER0 S T AE1 N D");
+
+        assert_eq!(cinema.stress, "100");
+        assert_eq!(cinema.syllables, 3);
+        assert_eq!(understand.stress, "201");
+        assert_eq!(understand.syllables, 3);
+    }
+
+    #[test]
+    fn rhyme_starts_at_last_primary_stress(){
+        assert_eq!(Pronunciation::new("K AE1 T").rhyme, "AE1 T");
+        assert_eq!(Pronunciation::new("HH AE1 T").rhyme, "AE1 T");
+        assert_eq!(Pronunciation::new("S IH1 N AH0 M AH0").rhyme, "IH1 N AH0 M AH0");
+        assert_eq!(Pronunciation::new("AH2 N D ER0 S T AE1 N D").rhyme, "AE1 N D");
+    }
+
+    #[test]
+    fn rhyme_without_primary_stress(){
+        let the: Pronunciation = Pronunciation::new("DH AH0");
+
+        assert_eq!(the.stress, "0");
+        assert_eq!(the.rhyme, "AH0");
+    }
+}
@@ -0,0 +1,294 @@
+use anyhow::Result;
+use crate::dictionary_handler::{Definition, Word};
+use crate::output_handler::{OutputWriter, Relation, SenseRelation, WordSense};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Prefixes of the vocabularies used, written at the top of Turtle output
+const PREFIXES: [(&str, &str); 5] = [
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("ontolex", "http://www.w3.org/ns/lemon/ontolex#"),
+    ("skos", "http://www.w3.org/2004/02/skos/core#"),
+    ("wn", "https://globalwordnet.github.io/schemas/wn#")
+];
+
+/// Namespace of Interlingual Index concepts
+const ILI_NAMESPACE: &str = "http://globalwordnet.org/ili/";
+
+/// Serializations the RDF writer can produce
+#[derive(Clone, Copy, PartialEq)]
+pub enum Syntax{
+    Turtle,
+    NTriples
+}
+
+/// Object of a triple
+enum Term{
+    /// Full IRI of a resource
+    Iri(String),
+    /// Prefixed name of a vocabulary term (e.g. ontolex:LexicalEntry)
+    Name(String),
+    /// English string literal
+    Literal(String),
+    /// String literal without a language tag, for identifiers
+    Plain(String)
+}
+
+/// Predicates and objects of a subject
+type Properties<'a> = Vec<(&'a str, Term)>;
+
+
+/// Percent encode everything but unreserved characters, so lemmas and sense keys can be used in IRIs
+fn encode_iri(value: &str) -> String{
+    let mut encoded: String = String::new();
+
+    for byte in value.bytes(){
+        match byte{
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte))
+        }
+    }
+
+    encoded
+}
+
+
+/// Escape a value for use in a double quoted Turtle or N-Triples string
+fn escape_literal(value: &str) -> String{
+    value.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+
+/// Expand a prefixed name into a full IRI
+fn expand(name: &str) -> String{
+    let (prefix, local) = name.split_once(':').unwrap_or(("", name));
+
+    match PREFIXES.iter().find(|(known, _)| *known == prefix){
+        Some((_, namespace)) => format!("{}{}", namespace, local),
+        None => name.to_string()
+    }
+}
+
+
+/// Name of a relation in the Global WordNet Association schema
+fn wn_relation(relation_name: &str) -> &str{
+    match relation_name{
+        "member_holonym" => "holo_member",
+        "substance_holonym" => "holo_substance",
+        "part_holonym" => "holo_part",
+        "member_meronym" => "mero_member",
+        "substance_meronym" => "mero_substance",
+        "part_meronym" => "mero_part",
+        "member_topic" => "has_domain_topic",
+        "member_region" => "has_domain_region",
+        "domain_usage" => "exemplifies",
+        "member_usage" => "is_exemplified_by",
+        "entailment" => "entails",
+        "cause" => "causes",
+        "also_see" => "also",
+        "verb_group" | "similar_to" => "similar",
+        _ => relation_name
+    }
+}
+
+
+/// Writes words as OntoLex lexical entries, senses and concepts in Turtle or N-Triples.
+///
+/// Synsets get IRIs from their offset and part of speech, senses from their sense key where
+/// one could be computed, and entries from the lemma and part of speech.
+pub struct RdfWriter{
+    output_path: PathBuf,
+    syntax: Syntax,
+    base_uri: String,
+    concept_iris: HashMap<i64, String>,
+    sense_iris: HashMap<i64, String>,
+    statements: Vec<(String, Vec<(String, Term)>)>
+}
+
+impl RdfWriter{
+    pub fn new(output_path: &Path, syntax: Syntax, base_uri: &str) -> RdfWriter{
+        RdfWriter {
+            output_path: output_path.to_owned(),
+            syntax,
+            base_uri: base_uri.to_string(),
+            concept_iris: HashMap::new(),
+            sense_iris: HashMap::new(),
+            statements: vec![]
+        }
+    }
+
+    /// Add a subject with its predicates and objects
+    fn add(&mut self, subject: String, properties: Properties){
+        self.statements.push((subject, properties.into_iter().map(|(predicate, object)| (predicate.to_string(), object)).collect()));
+    }
+
+    fn format_term(&self, term: &Term) -> String{
+        match term{
+            Term::Iri(iri) => format!("<{}>", iri),
+            Term::Name(name) if self.syntax == Syntax::Turtle => name.clone(),
+            Term::Name(name) => format!("<{}>", expand(name)),
+            Term::Literal(value) => format!("\"{}\"@en", escape_literal(value)),
+            Term::Plain(value) => format!("\"{}\"", escape_literal(value))
+        }
+    }
+
+    fn serialize(&self) -> String{
+        let mut rdf: String = String::new();
+
+        match self.syntax{
+            Syntax::Turtle => {
+                for (prefix, namespace) in PREFIXES{
+                    rdf.push_str(&format!("@prefix {}: <{}> .\n", prefix, namespace));
+                }
+
+                // Group the predicates of each subject
+                for (subject, properties) in &self.statements{
+                    let lines: Vec<String> = properties.iter()
+                        .map(|(predicate, object)| format!("{} {}", predicate, self.format_term(object)))
+                        .collect();
+
+                    rdf.push_str(&format!("\n<{}>\n    {} .\n", subject, lines.join(" ;\n    ")));
+                }
+            }
+            Syntax::NTriples => {
+                for (subject, properties) in &self.statements{
+                    for (predicate, object) in properties{
+                        rdf.push_str(&format!("<{}> <{}> {} .\n", subject, expand(predicate), self.format_term(object)));
+                    }
+                }
+            }
+        }
+
+        rdf
+    }
+}
+
+impl OutputWriter for RdfWriter{
+    fn label(&self) -> &str{
+        match self.syntax{
+            Syntax::Turtle => "Turtle",
+            Syntax::NTriples => "N-Triples"
+        }
+    }
+
+    fn begin(&mut self) -> Result<()>{
+        // Print status message
+        println!("Creating {}...", self.label());
+
+        Ok(())
+    }
+
+    fn write_synset(&mut self, id: i64, definition: &Definition) -> Result<()>{
+        let concept_iri: String = format!("{}synset/{}", self.base_uri, definition.synset_id());
+
+        let mut properties: Properties = vec![
+            ("rdf:type", Term::Name(String::from("ontolex:LexicalConcept"))),
+            ("skos:definition", Term::Literal(definition.data.clone())),
+            ("wn:partOfSpeech", Term::Name(format!("wn:{}", definition.part_of_speech.name().replace(' ', "_"))))
+        ];
+
+        // Link the concept to the same concept in other wordnets
+        if let Some(ili) = &definition.ili{
+            properties.push(("wn:ili", Term::Iri(format!("{}{}", ILI_NAMESPACE, encode_iri(ili)))));
+        }
+
+        self.add(concept_iri.clone(), properties);
+
+        self.concept_iris.insert(id, concept_iri);
+
+        Ok(())
+    }
+
+    fn write_word(&mut self, _id: i64, word: &str, entry: &Word, senses: &[WordSense]) -> Result<()>{
+        // Write an entry for each part of speech the word has, in the order of its senses
+        let mut entries: Vec<(String, String, Properties)> = vec![];
+
+        for sense in senses{
+            let synset_id: String = sense.definition.synset_id();
+            let letter: &str = synset_id.split_once('-').map_or("", |(_, letter)| letter);
+
+            // Use the sense key where there is one
+            let sense_iri: String = match &sense.sense.sense_key{
+                Some(sense_key) => format!("{}sense/{}", self.base_uri, encode_iri(sense_key)),
+                None => format!("{}sense/{}-{}", self.base_uri, encode_iri(word), synset_id)
+            };
+
+            let mut sense_properties: Properties = vec![
+                ("rdf:type", Term::Name(String::from("ontolex:LexicalSense"))),
+                ("ontolex:isLexicalizedSenseOf", Term::Iri(self.concept_iris[&sense.synset_id].clone()))
+            ];
+
+            if let Some(sense_key) = &sense.sense.sense_key{
+                sense_properties.push(("wn:senseKey", Term::Plain(sense_key.clone())));
+            }
+
+            self.add(sense_iri.clone(), sense_properties);
+            self.sense_iris.insert(sense.id, sense_iri.clone());
+
+            // Satellites share an entry with the head adjectives
+            match entries.iter_mut().find(|(entry_letter, _, _)| *entry_letter == letter){
+                Some((_, _, properties)) => properties.push(("ontolex:sense", Term::Iri(sense_iri))),
+                None => {
+                    let entry_iri: String = format!("{}entry/{}-{}", self.base_uri, encode_iri(word), letter);
+
+                    entries.push((letter.to_string(), entry_iri, vec![
+                        ("rdf:type", Term::Name(String::from("ontolex:LexicalEntry"))),
+                        ("rdfs:label", Term::Literal(entry.display.clone())),
+                        ("wn:partOfSpeech", Term::Name(format!("wn:{}", sense.definition.part_of_speech.name().replace(' ', "_")))),
+                        ("ontolex:canonicalForm", Term::Iri(format!("{}entry/{}-{}#form", self.base_uri, encode_iri(word), letter))),
+                        ("ontolex:sense", Term::Iri(sense_iri))
+                    ]));
+                }
+            }
+        }
+
+        for (_, entry_iri, properties) in entries{
+            self.add(format!("{}#form", entry_iri), vec![
+                ("rdf:type", Term::Name(String::from("ontolex:Form"))),
+                ("ontolex:writtenRep", Term::Literal(entry.display.clone()))
+            ]);
+            self.add(entry_iri, properties);
+        }
+
+        Ok(())
+    }
+
+    fn write_relation(&mut self, relation: &Relation) -> Result<()>{
+        let target: String = self.concept_iris[&relation.target_id].clone();
+
+        self.add(self.concept_iris[&relation.source_id].clone(), vec![
+            (&format!("wn:{}", wn_relation(relation.pointer.relation_name())), Term::Iri(target))
+        ]);
+
+        Ok(())
+    }
+
+    fn write_sense_relation(&mut self, relation: &SenseRelation) -> Result<()>{
+        let target: String = self.sense_iris[&relation.target_id].clone();
+
+        self.add(self.sense_iris[&relation.source_id].clone(), vec![
+            (&format!("wn:{}", wn_relation(relation.pointer.relation_name())), Term::Iri(target))
+        ]);
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()>{
+        let file_name: &str = match self.syntax{
+            Syntax::Turtle => "dictionary.ttl",
+            Syntax::NTriples => "dictionary.nt"
+        };
+
+        // Save to file
+        let mut file = File::create(self.output_path.join(file_name))?;
+        file.write_all(self.serialize().as_bytes())?;
+
+        Ok(())
+    }
+}
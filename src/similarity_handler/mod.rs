@@ -0,0 +1,364 @@
+use anyhow::{bail, Result};
+use crate::PartOfSpeech;
+use crate::dictionary_handler::{self, Definition, Definitions, SynsetKey};
+use crate::file_handler::{self, IndexDataPair};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Similarity measures between two synsets
+#[derive(Clone, Copy)]
+pub enum Measure{
+    /// 1 / (shortest hypernym path length + 1)
+    Path,
+    /// Leacock-Chodorow: -ln(shortest path nodes / (2 * taxonomy depth))
+    LeacockChodorow,
+    /// Wu-Palmer: 2 * depth(lcs) / (depth(a) + depth(b))
+    WuPalmer,
+    /// Resnik: information content of the least common subsumer
+    Resnik,
+    /// Jiang-Conrath: 1 / (IC(a) + IC(b) - 2 * IC(lcs))
+    JiangConrath,
+    /// Lin: 2 * IC(lcs) / (IC(a) + IC(b))
+    Lin
+}
+
+impl Measure{
+    pub const ALL: [Measure; 6] = [Measure::Path, Measure::LeacockChodorow, Measure::WuPalmer, Measure::Resnik, Measure::JiangConrath, Measure::Lin];
+
+    pub fn name(&self) -> &str{
+        match self{
+            Measure::Path => "path",
+            Measure::LeacockChodorow => "lch",
+            Measure::WuPalmer => "wup",
+            Measure::Resnik => "resnik",
+            Measure::JiangConrath => "jcn",
+            Measure::Lin => "lin"
+        }
+    }
+}
+
+
+/// Every synset held in memory with what's needed to compare them.
+///
+/// Measures only compare synsets of the same part of speech that share an ancestor through
+/// hypernym or instance hypernym pointers, and return None otherwise.
+/// Information content comes from the sense tag counts with add-one smoothing, so synsets
+/// never seen in the concordances still have a finite value.
+pub struct SynsetGraph{
+    definitions: Definitions,
+    lemmas: HashMap<String, Vec<SynsetKey>>,
+    information_content: HashMap<SynsetKey, f64>,
+    taxonomy_depths: HashMap<PartOfSpeech, u32>,
+    has_tag_counts: bool
+}
+
+impl SynsetGraph{
+    /// Build the graph from synsets whose ancestors have been found, and the sense tag counts
+    pub fn new(definitions: Definitions, tag_counts: &HashMap<String, u32>) -> SynsetGraph{
+        // Map every lemma to the synsets it's in
+        let mut lemmas: HashMap<String, Vec<SynsetKey>> = HashMap::new();
+
+        for (key, definition) in &definitions{
+            for word in &definition.words{
+                lemmas.entry(dictionary_handler::base_lemma(&word.data)).or_default().push(*key);
+            }
+        }
+
+        for keys in lemmas.values_mut(){
+            keys.sort();
+        }
+
+        // Count every synset's own tags, then add them to each of its ancestors
+        let mut frequencies: HashMap<&SynsetKey, f64> = HashMap::new();
+        let mut totals: HashMap<PartOfSpeech, f64> = HashMap::new();
+
+        for (key, definition) in &definitions{
+            let count: f64 = 1.0 + definition.words.iter()
+                .filter_map(|word| dictionary_handler::get_sense_key(&dictionary_handler::base_lemma(&word.data), definition, None))
+                .filter_map(|sense_key| tag_counts.get(&sense_key))
+                .map(|count| *count as f64)
+                .sum::<f64>();
+
+            *frequencies.entry(key).or_default() += count;
+            *totals.entry(key.0).or_default() += count;
+
+            for (ancestor, _) in &definition.ancestors{
+                *frequencies.entry(ancestor).or_default() += count;
+            }
+        }
+
+        // Information content is the negative log of the share of its part of speech's tags
+        let information_content: HashMap<SynsetKey, f64> = frequencies.iter()
+            .map(|(key, frequency)| (**key, -(frequency / totals[&key.0]).ln()))
+            .collect();
+
+        // Get the deepest synset of each part of speech
+        let mut taxonomy_depths: HashMap<PartOfSpeech, u32> = HashMap::new();
+
+        for (key, definition) in &definitions{
+            let depth: u32 = definition.ancestors.iter().map(|(_, distance)| *distance).max().unwrap_or(0);
+            let entry: &mut u32 = taxonomy_depths.entry(key.0).or_default();
+            *entry = (*entry).max(depth);
+        }
+
+        SynsetGraph { definitions, lemmas, information_content, taxonomy_depths, has_tag_counts: !tag_counts.is_empty() }
+    }
+
+    /// Read every synset from the data files and the tag counts from the WordNet directory
+    pub fn load(index_data_pairs: &Vec<IndexDataPair>, dir_path: &Path) -> Result<SynsetGraph>{
+        let definitions: Definitions = dictionary_handler::read_all_definitions(index_data_pairs, dir_path)?;
+        let tag_counts: HashMap<String, u32> = file_handler::read_tag_counts(dir_path)?;
+
+        Ok(SynsetGraph::new(definitions, &tag_counts))
+    }
+
+    pub fn definition(&self, key: &SynsetKey) -> Option<&Definition>{
+        self.definitions.get(key)
+    }
+
+    /// Whether information content comes from sense tag counts, rather than add-one smoothing alone
+    pub fn has_tag_counts(&self) -> bool{
+        self.has_tag_counts
+    }
+
+    /// Synsets a word is in, written either way (e.g. Ice Cream or ice_cream)
+    pub fn synsets(&self, word: &str) -> &[SynsetKey]{
+        self.lemmas.get(&dictionary_handler::normalize_word(word)).map_or(&[], |keys| keys.as_slice())
+    }
+
+    pub fn similarity(&self, measure: Measure, a: &SynsetKey, b: &SynsetKey) -> Option<f64>{
+        match measure{
+            Measure::Path => self.path_similarity(a, b),
+            Measure::LeacockChodorow => self.lch_similarity(a, b),
+            Measure::WuPalmer => self.wup_similarity(a, b),
+            Measure::Resnik => self.resnik_similarity(a, b),
+            Measure::JiangConrath => self.jcn_similarity(a, b),
+            Measure::Lin => self.lin_similarity(a, b)
+        }
+    }
+
+    pub fn path_similarity(&self, a: &SynsetKey, b: &SynsetKey) -> Option<f64>{
+        let distance: u32 = self.shortest_path(a, b)?;
+
+        Some(1.0 / (distance as f64 + 1.0))
+    }
+
+    pub fn lch_similarity(&self, a: &SynsetKey, b: &SynsetKey) -> Option<f64>{
+        let distance: u32 = self.shortest_path(a, b)?;
+        let depth: f64 = self.taxonomy_depths.get(&a.0).copied().unwrap_or(0) as f64 + 1.0;
+
+        Some(-((distance as f64 + 1.0) / (2.0 * depth)).ln())
+    }
+
+    pub fn wup_similarity(&self, a: &SynsetKey, b: &SynsetKey) -> Option<f64>{
+        // Use the deepest common subsumer, counting depth in synsets rather than links
+        let (subsumer, distance_a, distance_b) = self.common_subsumers(a, b).into_iter()
+            .max_by_key(|(subsumer, distance_a, distance_b)| (self.definitions.get(*subsumer).and_then(|definition| definition.depth), std::cmp::Reverse(distance_a + distance_b)))?;
+
+        let depth: f64 = self.definitions.get(subsumer)?.depth? as f64 + 1.0;
+
+        Some(2.0 * depth / (distance_a as f64 + distance_b as f64 + 2.0 * depth))
+    }
+
+    pub fn resnik_similarity(&self, a: &SynsetKey, b: &SynsetKey) -> Option<f64>{
+        self.subsumer_information_content(a, b)
+    }
+
+    pub fn jcn_similarity(&self, a: &SynsetKey, b: &SynsetKey) -> Option<f64>{
+        let subsumer: f64 = self.subsumer_information_content(a, b)?;
+        let distance: f64 = self.information_content.get(a)? + self.information_content.get(b)? - 2.0 * subsumer;
+
+        // Identical information content is as similar as synsets can be
+        if distance <= 0.0{
+            return Some(f64::INFINITY);
+        }
+
+        Some(1.0 / distance)
+    }
+
+    pub fn lin_similarity(&self, a: &SynsetKey, b: &SynsetKey) -> Option<f64>{
+        let subsumer: f64 = self.subsumer_information_content(a, b)?;
+        let total: f64 = self.information_content.get(a)? + self.information_content.get(b)?;
+
+        if total <= 0.0{
+            return Some(1.0);
+        }
+
+        Some(2.0 * subsumer / total)
+    }
+
+    /// Best score between any synset of one word and any synset of another, with the synsets that gave it
+    pub fn word_similarity(&self, measure: Measure, word_a: &str, word_b: &str) -> Option<(f64, &SynsetKey, &SynsetKey)>{
+        let mut best: Option<(f64, &SynsetKey, &SynsetKey)> = None;
+
+        for a in self.synsets(word_a){
+            for b in self.synsets(word_b){
+                if let Some(score) = self.similarity(measure, a, b){
+                    if best.is_none_or(|(best_score, _, _)| score > best_score){
+                        best = Some((score, a, b));
+                    }
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Synsets both synsets are or are below, with the distance to each
+    fn common_subsumers<'a>(&'a self, a: &'a SynsetKey, b: &'a SynsetKey) -> Vec<(&'a SynsetKey, u32, u32)>{
+        // Synsets of different parts of speech are never compared
+        if a.0 != b.0{
+            return vec![];
+        }
+
+        let (Some(definition_a), Some(definition_b)) = (self.definitions.get(a), self.definitions.get(b)) else{
+            return vec![];
+        };
+
+        // Include the synsets themselves at a distance of zero
+        let ancestors_b: HashMap<&SynsetKey, u32> = definition_b.ancestors.iter()
+            .map(|(key, distance)| (key, *distance))
+            .chain([(b, 0)])
+            .collect();
+
+        definition_a.ancestors.iter()
+            .map(|(key, distance)| (key, *distance))
+            .chain([(a, 0)])
+            .filter_map(|(key, distance_a)| ancestors_b.get(key).map(|distance_b| (key, distance_a, *distance_b)))
+            .collect()
+    }
+
+    /// Fewest hypernym/hyponym links between two synsets through a common subsumer
+    fn shortest_path(&self, a: &SynsetKey, b: &SynsetKey) -> Option<u32>{
+        self.common_subsumers(a, b).into_iter()
+            .map(|(_, distance_a, distance_b)| distance_a + distance_b)
+            .min()
+    }
+
+    /// Information content of the most informative common subsumer
+    fn subsumer_information_content(&self, a: &SynsetKey, b: &SynsetKey) -> Option<f64>{
+        self.common_subsumers(a, b).into_iter()
+            .filter_map(|(subsumer, _, _)| self.information_content.get(subsumer).copied())
+            .reduce(f64::max)
+    }
+}
+
+
+pub fn print_word_similarity(index_data_pairs: &Vec<IndexDataPair>, dir_path: &Path, word_a: &str, word_b: &str) -> Result<()>{
+    // Load every synset
+    let graph: SynsetGraph = SynsetGraph::load(index_data_pairs, dir_path)?;
+
+    // Throw an error if either word isn't in WordNet
+    for word in [word_a, word_b]{
+        if graph.synsets(word).is_empty(){
+            bail!(format!("{} was not found!", word).red())
+        }
+    }
+
+    // Print the best score of every measure
+    println!("{}", format!("Similarity of {} and {}", word_a, word_b).green());
+
+    for measure in Measure::ALL{
+        match graph.word_similarity(measure, word_a, word_b){
+            Some((score, a, b)) => {
+                let gloss = |key: &SynsetKey| graph.definition(key).map_or(String::new(), |definition| definition.data.clone());
+                println!("{:<8}{:>10.4}  ({}: {} / {}: {})", measure.name(), score, word_a, gloss(a), word_b, gloss(b));
+            }
+            None => println!("{:<8}{:>10}", measure.name(), "n/a")
+        }
+    }
+
+    // Warn that the information content measures only reflect the shape of the hierarchy
+    if !graph.has_tag_counts(){
+        println!("{}", "No cntlist.rev or cntlist found, so resnik, jcn and lin use add-one smoothed information content only".yellow());
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use regex::Regex;
+
+    /// entity > animal > dog (also hot dog) and cat, entity > plant, and an unrelated verb
+    const DATA_LINES: [&str; 5] = [
+        "00000100 03 n 01 entity 0 000 | that which exists",
+        "00000200 03 n 01 animal 0 001 @ 00000100 n 0000 | a living organism",
+        "00000300 05 n 02 dog 0 hot_dog 0 001 @ 00000200 n 0000 | a domestic dog",
+        "00000400 05 n 01 cat 0 001 @ 00000200 n 0000 | a feline",
+        "00000500 03 n 01 plant 0 001 @ 00000100 n 0000 | a living organism lacking locomotion"
+    ];
+
+    const DOG: SynsetKey = (PartOfSpeech::Noun, 300);
+    const CAT: SynsetKey = (PartOfSpeech::Noun, 400);
+    const PLANT: SynsetKey = (PartOfSpeech::Noun, 500);
+
+    fn graph() -> SynsetGraph{
+        let definition_regex: Regex = Regex::new(dictionary_handler::DEFINITION_PATTERN).unwrap();
+        let mut definitions: Definitions = Definitions::new();
+
+        for line in DATA_LINES{
+            let definition: Definition = dictionary_handler::parse_data_line(line, PartOfSpeech::Noun, &definition_regex).unwrap();
+            definitions.insert((PartOfSpeech::Noun, definition.offset), definition);
+        }
+
+        let run: Definition = dictionary_handler::parse_data_line("00000600 38 v 01 run 0 000 00 | move fast", PartOfSpeech::Verb, &definition_regex).unwrap();
+        definitions.insert((PartOfSpeech::Verb, 600), run);
+
+        dictionary_handler::add_closure(&mut definitions);
+
+        SynsetGraph::new(definitions, &HashMap::new())
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64){
+        let actual: f64 = actual.expect("no score");
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn path_based_measures(){
+        let graph: SynsetGraph = graph();
+
+        // Two links through animal, in a taxonomy three synsets deep
+        assert_close(graph.path_similarity(&DOG, &CAT), 1.0 / 3.0);
+        assert_close(graph.lch_similarity(&DOG, &CAT), (2.0f64).ln());
+        assert_close(graph.wup_similarity(&DOG, &CAT), 2.0 / 3.0);
+        assert_close(graph.path_similarity(&DOG, &PLANT), 0.25);
+        assert_close(graph.path_similarity(&DOG, &DOG), 1.0);
+    }
+
+    #[test]
+    fn information_content_measures(){
+        let graph: SynsetGraph = graph();
+
+        // Each synset counts once, so animal covers 3 of the 5 and each leaf 1 of the 5
+        let animal: f64 = (5.0f64 / 3.0).ln();
+        let leaf: f64 = (5.0f64).ln();
+
+        assert!(!graph.has_tag_counts());
+        assert_close(graph.resnik_similarity(&DOG, &CAT), animal);
+        assert_close(graph.jcn_similarity(&DOG, &CAT), 1.0 / (2.0 * leaf - 2.0 * animal));
+        assert_close(graph.lin_similarity(&DOG, &CAT), animal / leaf);
+        assert_close(graph.resnik_similarity(&DOG, &PLANT), 0.0);
+    }
+
+    #[test]
+    fn different_parts_of_speech_are_not_compared(){
+        let graph: SynsetGraph = graph();
+
+        assert!(graph.path_similarity(&DOG, &(PartOfSpeech::Verb, 600)).is_none());
+        assert!(graph.word_similarity(Measure::Lin, "dog", "run").is_none());
+    }
+
+    #[test]
+    fn words_are_normalized(){
+        let graph: SynsetGraph = graph();
+
+        assert_eq!(graph.synsets("Dog"), &[DOG]);
+        assert_eq!(graph.synsets(" hot dog "), &[DOG]);
+        assert_close(graph.word_similarity(Measure::Path, "Hot Dog", "CAT").map(|(score, _, _)| score), 1.0 / 3.0);
+    }
+}
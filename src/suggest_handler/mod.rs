@@ -0,0 +1,268 @@
+use anyhow::{bail, Result};
+use crate::Args;
+use crate::dictionary_handler::{self, WordData, Words};
+use crate::file_handler::{self, IndexDataPair};
+use colored::Colorize;
+use std::collections::{BTreeMap, HashMap};
+
+
+/// Number of single character insertions, deletions and substitutions needed to turn one word into another
+pub fn levenshtein(a: &str, b: &str) -> usize{
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate(){
+        let mut current: Vec<usize> = vec![i + 1];
+
+        for (j, b_char) in b_chars.iter().enumerate(){
+            let cost: usize = if a_char == *b_char { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b_chars.len()]
+}
+
+
+/// A node of the BK-tree, with children keyed by their distance from it
+struct Node{
+    word: String,
+    children: HashMap<usize, usize>
+}
+
+/// BK-tree over words, for finding every word within an edit distance without comparing against all of them
+#[derive(Default)]
+pub struct BkTree{
+    nodes: Vec<Node>
+}
+
+impl BkTree{
+    pub fn new() -> BkTree{
+        BkTree::default()
+    }
+
+    pub fn insert(&mut self, word: &str){
+        let new_index: usize = self.nodes.len();
+
+        if new_index == 0{
+            self.nodes.push(Node { word: word.to_string(), children: HashMap::new() });
+            return;
+        }
+
+        // Walk down the children at the word's distance from each node until there's a free slot
+        let mut index: usize = 0;
+
+        loop{
+            let distance: usize = levenshtein(word, &self.nodes[index].word);
+
+            // Skip words already in the tree
+            if distance == 0{
+                return;
+            }
+
+            match self.nodes[index].children.get(&distance){
+                Some(child) => index = *child,
+                None => {
+                    self.nodes[index].children.insert(distance, new_index);
+                    self.nodes.push(Node { word: word.to_string(), children: HashMap::new() });
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Every word within max_distance of a word, with its distance
+    pub fn find(&self, word: &str, max_distance: usize) -> Vec<(&str, usize)>{
+        let mut found: Vec<(&str, usize)> = vec![];
+        let mut pending: Vec<usize> = if self.nodes.is_empty() { vec![] } else { vec![0] };
+
+        while let Some(index) = pending.pop(){
+            let node: &Node = &self.nodes[index];
+            let distance: usize = levenshtein(word, &node.word);
+
+            if distance <= max_distance{
+                found.push((&node.word, distance));
+            }
+
+            // Only children within max_distance of this distance can be close enough
+            for (child_distance, child) in &node.children{
+                if child_distance.abs_diff(distance) <= max_distance{
+                    pending.push(*child);
+                }
+            }
+        }
+
+        found
+    }
+}
+
+
+/// Finds the closest lemmas to a possibly misspelled word.
+///
+/// Inflected forms from the morphological exception lists (e.g. ran for run) are searched too, so near misses of
+/// irregular forms suggest their base lemma.
+pub struct Suggester{
+    tree: BkTree,
+    lemmas: HashMap<String, Vec<String>>
+}
+
+impl Suggester{
+    /// Build from the saved words and the exception lists, keyed by inflected form
+    pub fn new(words: &Words, exceptions: &HashMap<String, Vec<String>>) -> Suggester{
+        let mut tree: BkTree = BkTree::new();
+        let mut lemmas: HashMap<String, Vec<String>> = HashMap::new();
+
+        for word in words.keys(){
+            tree.insert(word);
+            lemmas.entry(word.clone()).or_default().push(word.clone());
+        }
+
+        // Only add inflected forms of lemmas that were saved
+        for (form, bases) in exceptions{
+            let kept_bases: Vec<&String> = bases.iter().filter(|base| words.contains_key(*base)).collect();
+
+            if kept_bases.is_empty(){
+                continue;
+            }
+
+            tree.insert(form);
+            lemmas.entry(form.clone()).or_default().extend(kept_bases.into_iter().cloned());
+        }
+
+        Suggester { tree, lemmas }
+    }
+
+    /// Lemmas within max_distance of a word, closest first and then alphabetically
+    pub fn suggest(&self, word: &str, max_distance: usize) -> Vec<(String, usize)>{
+        // Match the word in the form used by the index files
+        let word: String = dictionary_handler::normalize_word(word);
+
+        // Keep the closest distance to each lemma
+        let mut closest: BTreeMap<String, usize> = BTreeMap::new();
+
+        for (form, distance) in self.tree.find(&word, max_distance){
+            for lemma in self.lemmas.get(form).into_iter().flatten(){
+                let entry: &mut usize = closest.entry(lemma.clone()).or_insert(distance);
+                *entry = (*entry).min(distance);
+            }
+        }
+
+        let mut suggestions: Vec<(String, usize)> = closest.into_iter().collect();
+        suggestions.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+
+        suggestions
+    }
+}
+
+
+pub fn print_suggestions(index_data_pairs: &Vec<IndexDataPair>, args: &Args, word: &str, max_distance: usize, limit: usize) -> Result<()>{
+    // Get the words the filters keep, and the inflected forms of them
+    let (_, words): WordData = dictionary_handler::get_word_data(index_data_pairs, args)?;
+    let exceptions: HashMap<String, Vec<String>> = file_handler::read_exceptions(&args.directory)?;
+    let suggester: Suggester = Suggester::new(&words, &exceptions);
+
+    let suggestions: Vec<(String, usize)> = suggester.suggest(word, max_distance);
+
+    // Throw an error if nothing was close enough
+    if suggestions.is_empty(){
+        bail!(format!("No words found within {} edits of {}!", max_distance, word).red())
+    }
+
+    match suggestions.first(){
+        Some((lemma, 0)) => println!("{}", format!("{} is in WordNet as {}", word, lemma.replace('_', " ")).green()),
+        _ => println!("{}", "Did you mean...?".green())
+    }
+
+    for (lemma, distance) in suggestions.iter().take(limit){
+        println!("{:<30}{}", lemma.replace('_', " "), distance);
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::dictionary_handler::Word;
+
+    const WORDS: [&str; 12] = ["book", "books", "boo", "boon", "cook", "cake", "cape", "cart", "boat", "coat", "goat", "ice_cream"];
+
+    #[test]
+    fn known_distances(){
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+        assert_eq!(levenshtein("saturday", "sunday"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn tree_matches_brute_force(){
+        let mut tree: BkTree = BkTree::new();
+
+        for word in WORDS{
+            tree.insert(word);
+        }
+
+        for query in ["book", "bake", "coast", "ice_crem", "zzz", ""]{
+            for max_distance in 0..=4{
+                let mut found: Vec<(&str, usize)> = tree.find(query, max_distance);
+                found.sort();
+
+                let mut expected: Vec<(&str, usize)> = WORDS.iter()
+                    .map(|word| (*word, levenshtein(query, word)))
+                    .filter(|(_, distance)| *distance <= max_distance)
+                    .collect();
+                expected.sort();
+
+                assert_eq!(found, expected, "{} within {}", query, max_distance);
+            }
+        }
+    }
+
+    #[test]
+    fn suggests_base_lemmas_of_exceptions(){
+        let mut words: Words = Words::new();
+
+        for word in ["run", "go", "goose"]{
+            words.insert(word.to_string(), Word::default());
+        }
+
+        let exceptions: HashMap<String, Vec<String>> = HashMap::from([
+            (String::from("ran"), vec![String::from("run")]),
+            (String::from("geese"), vec![String::from("goose")]),
+            (String::from("oxen"), vec![String::from("ox")])
+        ]);
+
+        let suggester: Suggester = Suggester::new(&words, &exceptions);
+
+        assert_eq!(suggester.suggest("Rann", 1), [(String::from("run"), 1)]);
+        assert_eq!(suggester.suggest("geeze", 1), [(String::from("goose"), 1)]);
+        assert!(suggester.suggest("oxen", 1).is_empty());
+    }
+
+    #[test]
+    fn forms_with_several_bases_are_inserted_once(){
+        let mut words: Words = Words::new();
+
+        for word in ["axis", "axe", "ax"]{
+            words.insert(word.to_string(), Word::default());
+        }
+
+        let exceptions: HashMap<String, Vec<String>> = HashMap::from([
+            (String::from("axes"), vec![String::from("axis"), String::from("axe"), String::from("ax")])
+        ]);
+
+        let suggester: Suggester = Suggester::new(&words, &exceptions);
+
+        assert_eq!(suggester.tree.nodes.len(), 4);
+
+        // Ties are broken alphabetically
+        assert_eq!(suggester.suggest("axes", 0), [(String::from("ax"), 0), (String::from("axe"), 0), (String::from("axis"), 0)]);
+    }
+}